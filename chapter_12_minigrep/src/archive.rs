@@ -0,0 +1,123 @@
+use crate::error::RunError;
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::path::Path;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &Path) -> Option<ArchiveKind> {
+        let file_name = path.file_name()?.to_str()?;
+        if file_name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if file_name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// A regular file of an archive.
+#[derive(Debug, PartialEq)]
+pub struct ArchiveMember {
+    /// Path of the file relative to the root of the archive.
+    pub path: String,
+    pub content: Vec<u8>,
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+/// Reads every regular file of a `.tar`, `.tar.gz`/`.tgz` or `.zip` archive, in archive order.
+pub fn read_members(archive_path: &Path) -> Result<Vec<ArchiveMember>, RunError> {
+    let kind = ArchiveKind::from_path(archive_path).ok_or_else(|| {
+        RunError::BadConfig(format!(
+            "Error: `{}` is not a tar or zip archive.",
+            archive_path.display()
+        ))
+    })?;
+    let file =
+        File::open(archive_path).map_err(|error| RunError::from_io_error(error, archive_path))?;
+    let members = match kind {
+        ArchiveKind::Tar => read_tar(file),
+        ArchiveKind::TarGz => read_tar(GzDecoder::new(file)),
+        ArchiveKind::Zip => read_zip(file),
+    };
+    members.map_err(|error| match error.kind() {
+        ErrorKind::NotFound | ErrorKind::PermissionDenied | ErrorKind::IsADirectory => {
+            RunError::from_io_error(error, archive_path)
+        }
+        kind => RunError::InputOutputError(io::Error::new(
+            kind,
+            format!("Cannot read archive `{}`: {error}", archive_path.display()),
+        )),
+    })
+}
+
+fn read_tar(reader: impl Read) -> io::Result<Vec<ArchiveMember>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.display().to_string();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        members.push(ArchiveMember { path, content });
+    }
+    Ok(members)
+}
+
+fn read_zip(file: File) -> io::Result<Vec<ArchiveMember>> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut members = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let path = entry.name().to_string();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        members.push(ArchiveMember { path, content });
+    }
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_kind_from_path() {
+        let kind = |path: &str| ArchiveKind::from_path(Path::new(path));
+        assert_eq!(kind("dist/bundle.tar"), Some(ArchiveKind::Tar));
+        assert_eq!(kind("bundle.tar.gz"), Some(ArchiveKind::TarGz));
+        assert_eq!(kind("bundle.tgz"), Some(ArchiveKind::TarGz));
+        assert_eq!(kind("bundle.zip"), Some(ArchiveKind::Zip));
+        assert_eq!(kind("bundle.gz"), None);
+        assert_eq!(kind("poem.txt"), None);
+    }
+
+    #[test]
+    fn read_tar_and_zip_members() {
+        for archive in ["bundle.tar", "bundle.tar.gz", "bundle.zip"] {
+            let members =
+                read_members(&Path::new("tests/fixtures/archives").join(archive)).unwrap();
+            let paths: Vec<&str> = members.iter().map(|member| member.path.as_str()).collect();
+            assert_eq!(paths, vec!["README.md", "src/lib.rs"], "{archive}");
+        }
+    }
+}
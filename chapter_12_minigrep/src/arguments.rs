@@ -0,0 +1,411 @@
+use crate::error::RunError;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArgumentValue {
+    None,
+    Text(&'static str),
+    Choices(&'static str, &'static [&'static str]),
+    FileType,
+    Path,
+    Directory,
+}
+
+impl ArgumentValue {
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            ArgumentValue::None => None,
+            ArgumentValue::Text(name) | ArgumentValue::Choices(name, _) => Some(name),
+            ArgumentValue::FileType => Some("TYPE"),
+            ArgumentValue::Path => Some("FILE"),
+            ArgumentValue::Directory => Some("DIRECTORY"),
+        }
+    }
+}
+
+/// Definition of a command line option, used to parse the arguments with `parse` and to generate
+/// the shell completions and the man page.
+#[derive(Debug, PartialEq)]
+pub struct Argument {
+    pub short: Option<char>,
+    pub long: &'static str,
+    pub value: ArgumentValue,
+    pub help: &'static str,
+}
+
+impl Argument {
+    pub fn matches(&self, argument: &str) -> bool {
+        match argument.strip_prefix("--") {
+            Some(long) => long == self.long,
+            None => {
+                let mut characters = argument.chars();
+                characters.next() == Some('-')
+                    && characters.next() == self.short
+                    && characters.next().is_none()
+                    && self.short.is_some()
+            }
+        }
+    }
+
+    /// The definition of the option `argument` in `definitions`, `--name=value` being the same
+    /// as `--name value`.
+    pub fn find_in(definitions: &'static [Argument], argument: &str) -> Option<&'static Argument> {
+        let (name, _) = split_inline_value(argument);
+        definitions
+            .iter()
+            .find(|definition| definition.matches(name))
+    }
+
+    /// The definition of an option of a search.
+    pub fn find(argument: &str) -> Option<&'static Argument> {
+        Argument::find_in(&ARGUMENTS, argument)
+    }
+
+    /// The value of this option given as `argument`: the value of `--name=value`, the next of
+    /// `arguments`, or empty for a flag. `None` if it is missing or given to a flag.
+    pub fn value(
+        &self,
+        argument: &str,
+        arguments: &mut impl Iterator<Item = String>,
+    ) -> Option<String> {
+        match (self.value, split_inline_value(argument).1) {
+            (ArgumentValue::None, None) => Some(String::new()),
+            (ArgumentValue::None, Some(_)) => None,
+            (_, Some(value)) => Some(value.to_string()),
+            (_, None) => arguments.next(),
+        }
+    }
+}
+
+fn split_inline_value(argument: &str) -> (&str, Option<&str>) {
+    match argument.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+        _ => (argument, None),
+    }
+}
+
+/// An argument of a command line, parsed with the definitions of the options of the command.
+#[derive(Debug, PartialEq)]
+pub enum ParsedArgument {
+    /// An option with its value, empty for a flag.
+    Option(&'static Argument, String),
+    Positional(String),
+}
+
+/// Parses the options of `definitions` and the positional arguments, in any order. The arguments
+/// after `--` are positional, and a missing value or a value given to a flag is `usage_error`.
+pub fn parse(
+    definitions: &'static [Argument],
+    mut arguments: impl Iterator<Item = String>,
+    usage_error: impl Fn() -> RunError,
+) -> Result<Vec<ParsedArgument>, RunError> {
+    let mut parsed_arguments = Vec::new();
+    while let Some(argument) = arguments.next() {
+        if argument == "--" {
+            parsed_arguments.extend(arguments.by_ref().map(ParsedArgument::Positional));
+            break;
+        }
+        let parsed_argument = match Argument::find_in(definitions, &argument) {
+            Some(definition) => {
+                let value = definition
+                    .value(&argument, &mut arguments)
+                    .ok_or_else(&usage_error)?;
+                ParsedArgument::Option(definition, value)
+            }
+            None => ParsedArgument::Positional(argument),
+        };
+        parsed_arguments.push(parsed_argument);
+    }
+    Ok(parsed_arguments)
+}
+
+/// Options given before the command, shared by every command.
+pub const GLOBAL_ARGUMENTS: [Argument; 2] = [
+    Argument {
+        short: None,
+        long: "color",
+        value: ArgumentValue::Choices("WHEN", &["auto", "always", "never"]),
+        help: "Color the output always, never or only in a terminal (auto)",
+    },
+    Argument {
+        short: None,
+        long: "no-history",
+        value: ArgumentValue::None,
+        help: "Do not record the search in the history, even with MINIGREP_HISTORY set",
+    },
+];
+
+/// Options of `minigrep serve`.
+pub const SERVE_ARGUMENTS: [Argument; 3] = [
+    Argument {
+        short: None,
+        long: "root",
+        value: ArgumentValue::Directory,
+        help: "Directory that can be searched (required)",
+    },
+    Argument {
+        short: None,
+        long: "port",
+        value: ArgumentValue::Text("PORT"),
+        help: "Port to listen on, 0 for any free port (required)",
+    },
+    Argument {
+        short: None,
+        long: "timeout",
+        value: ArgumentValue::Text("SECONDS"),
+        help: "Maximum time to receive a request and to send its response (default: 10)",
+    },
+];
+
+/// Options of a search, also given to `minigrep run` and after `minigrep save <name> --`.
+pub const ARGUMENTS: [Argument; 31] = [
+    Argument {
+        short: Some('t'),
+        long: "type",
+        value: ArgumentValue::FileType,
+        help: "Only search in files of this type (can be repeated)",
+    },
+    Argument {
+        short: Some('T'),
+        long: "type-not",
+        value: ArgumentValue::FileType,
+        help: "Do not search in files of this type (can be repeated)",
+    },
+    Argument {
+        short: None,
+        long: "type-add",
+        value: ArgumentValue::Text("NAME:GLOB"),
+        help: "Add a glob to a new or existing file type",
+    },
+    Argument {
+        short: None,
+        long: "interactive",
+        value: ArgumentValue::None,
+        help: "Browse the results in an interactive terminal interface",
+    },
+    Argument {
+        short: Some('s'),
+        long: "no-messages",
+        value: ArgumentValue::None,
+        help: "Skip the files that cannot be read without error messages",
+    },
+    Argument {
+        short: None,
+        long: "mmap",
+        value: ArgumentValue::None,
+        help: "Always memory-map the searched files",
+    },
+    Argument {
+        short: None,
+        long: "no-mmap",
+        value: ArgumentValue::None,
+        help: "Never memory-map the searched files",
+    },
+    Argument {
+        short: None,
+        long: "pre",
+        value: ArgumentValue::Text("COMMAND"),
+        help: "Search the output of COMMAND run with the path of each file, instead of the file",
+    },
+    Argument {
+        short: None,
+        long: "pre-glob",
+        value: ArgumentValue::Text("GLOB"),
+        help: "Only run the --pre command on the files whose name matches GLOB (can be repeated)",
+    },
+    Argument {
+        short: Some('a'),
+        long: "text",
+        value: ArgumentValue::None,
+        help: "Search and print binary files like text files",
+    },
+    Argument {
+        short: Some('z'),
+        long: "search-archives",
+        value: ArgumentValue::None,
+        help: "Search the files inside .tar, .tar.gz, .tgz and .zip archives",
+    },
+    Argument {
+        short: None,
+        long: "crlf",
+        value: ArgumentValue::None,
+        help: "Print each line with its own CRLF or LF terminator instead of a newline",
+    },
+    Argument {
+        short: None,
+        long: "null-data",
+        value: ArgumentValue::None,
+        help: "Lines are terminated by NUL instead of a newline",
+    },
+    Argument {
+        short: Some('0'),
+        long: "null",
+        value: ArgumentValue::None,
+        help: "Follow the file paths by NUL instead of a colon",
+    },
+    Argument {
+        short: None,
+        long: "binary-files",
+        value: ArgumentValue::Choices("TYPE", &["binary", "text", "without-match"]),
+        help: "Print `Binary file X matches` for binary files (binary), search them like text files (text) or skip them (without-match)",
+    },
+    Argument {
+        short: None,
+        long: "scope",
+        value: ArgumentValue::Choices("SCOPE", &["code", "comments", "strings"]),
+        help: "Only search the code, the comments or the string literals of Rust source code",
+    },
+    Argument {
+        short: None,
+        long: "lines",
+        value: ArgumentValue::Text("START:END"),
+        help: "Only search the lines from START to END (numbered from 1, both optional)",
+    },
+    Argument {
+        short: None,
+        long: "max-columns",
+        value: ArgumentValue::Text("NUM"),
+        help: "Truncate the printed lines longer than NUM bytes",
+    },
+    Argument {
+        short: Some('o'),
+        long: "only-matching",
+        value: ArgumentValue::None,
+        help: "Print only the matching text of each match",
+    },
+    Argument {
+        short: None,
+        long: "passthru",
+        value: ArgumentValue::None,
+        help: "Print every line and highlight the matches (reads stdin without a file path)",
+    },
+    Argument {
+        short: None,
+        long: "format",
+        value: ArgumentValue::Text("TEMPLATE"),
+        help: "Print each match with a template of {path}, {line}, {col}, {match} and {text}",
+    },
+    Argument {
+        short: None,
+        long: "field",
+        value: ArgumentValue::Text("FIELDS"),
+        help: "Only search in these comma-separated fields of delimited files (numbered from 1)",
+    },
+    Argument {
+        short: None,
+        long: "delimiter",
+        value: ArgumentValue::Text("DELIMITER"),
+        help: "Delimiter of the fields (default: a comma)",
+    },
+    Argument {
+        short: None,
+        long: "json-path",
+        value: ArgumentValue::Text("PATH"),
+        help: "Only search in the value at this key path of JSON lines",
+    },
+    Argument {
+        short: None,
+        long: "where",
+        value: ArgumentValue::Text("PREDICATE"),
+        help: "Only search in the JSON lines matching a path=value or path!=value predicate",
+    },
+    Argument {
+        short: None,
+        long: "boolean",
+        value: ArgumentValue::None,
+        help: "Parse the query as a boolean expression of terms with AND, OR, NOT and parentheses",
+    },
+    Argument {
+        short: None,
+        long: "diff",
+        value: ArgumentValue::Path,
+        help: "Only search the added and removed lines of a unified diff (- for stdin)",
+    },
+    Argument {
+        short: None,
+        long: "sort",
+        value: ArgumentValue::Choices("KEY", &["path", "modified", "accessed", "created"]),
+        help: "Sort the searched files by path or by modification, access or creation time",
+    },
+    Argument {
+        short: None,
+        long: "sortr",
+        value: ArgumentValue::Choices("KEY", &["path", "modified", "accessed", "created"]),
+        help: "Sort the searched files like --sort, in reverse order",
+    },
+    Argument {
+        short: None,
+        long: "unique",
+        value: ArgumentValue::None,
+        help: "Print each distinct matching line only once across all the files",
+    },
+    Argument {
+        short: None,
+        long: "stats",
+        value: ArgumentValue::None,
+        help: "Print statistics about the search after the results",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter;
+
+    #[test]
+    fn find_short_and_long_arguments() {
+        assert_eq!(
+            Argument::find("-t").map(|argument| argument.long),
+            Some("type")
+        );
+        assert_eq!(
+            Argument::find("--type").map(|argument| argument.long),
+            Some("type")
+        );
+        assert_eq!(
+            Argument::find("--stats").map(|argument| argument.long),
+            Some("stats")
+        );
+        assert!(Argument::find("-").is_none());
+        assert!(Argument::find("-ts").is_none());
+        assert!(Argument::find("--unknown").is_none());
+        assert!(Argument::find("type").is_none());
+        assert_eq!(
+            Argument::find("--type=rust").map(|argument| argument.long),
+            Some("type")
+        );
+        assert_eq!(
+            Argument::find_in(&GLOBAL_ARGUMENTS, "--color").map(|argument| argument.long),
+            Some("color")
+        );
+        assert!(Argument::find_in(&GLOBAL_ARGUMENTS, "--type").is_none());
+    }
+
+    #[test]
+    fn parse_options_and_positional_arguments() {
+        let arguments = ["--port=0", "--root", "src", "x", "--", "--port"];
+        let usage_error = || RunError::BadConfig("Usage".to_string());
+        let parsed_arguments = parse(
+            &SERVE_ARGUMENTS,
+            arguments.iter().map(|argument| argument.to_string()),
+            usage_error,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed_arguments,
+            vec![
+                ParsedArgument::Option(&SERVE_ARGUMENTS[1], "0".to_string()),
+                ParsedArgument::Option(&SERVE_ARGUMENTS[0], "src".to_string()),
+                ParsedArgument::Positional("x".to_string()),
+                ParsedArgument::Positional("--port".to_string()),
+            ]
+        );
+        let missing_value = parse(
+            &SERVE_ARGUMENTS,
+            iter::once("--root".to_string()),
+            usage_error,
+        );
+        assert!(matches!(missing_value, Err(RunError::BadConfig(_))));
+        let flag_value = parse(&ARGUMENTS, iter::once("--stats=1".to_string()), usage_error);
+        assert!(matches!(flag_value, Err(RunError::BadConfig(_))));
+    }
+}
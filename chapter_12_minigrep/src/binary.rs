@@ -0,0 +1,69 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Number of bytes at the start of a file in which a NUL byte marks the file as binary.
+pub const BINARY_SNIFF_SIZE: u64 = 8 * 1024;
+
+/// What to do with the binary files, like the `--binary-files` option of GNU grep.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum BinaryFiles {
+    /// Print `Binary file X matches` instead of the matching lines.
+    #[default]
+    Binary,
+    /// Search and print binary files like text files.
+    Text,
+    /// Skip binary files.
+    WithoutMatch,
+}
+
+impl BinaryFiles {
+    pub fn parse(value: &str) -> Result<BinaryFiles, String> {
+        match value {
+            "binary" => Ok(BinaryFiles::Binary),
+            "text" => Ok(BinaryFiles::Text),
+            "without-match" => Ok(BinaryFiles::WithoutMatch),
+            _ => Err(format!(
+                "Error: Unknown binary files policy `{value}`, expected one of: binary, text, without-match."
+            )),
+        }
+    }
+}
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    let sniff_size = bytes.len().min(BINARY_SNIFF_SIZE as usize);
+    bytes[..sniff_size].contains(&0)
+}
+
+/// Reads only the first block of the file to check if it is binary.
+pub fn is_binary_file(file_path: &Path) -> io::Result<bool> {
+    let mut first_block = Vec::new();
+    File::open(file_path)?
+        .take(BINARY_SNIFF_SIZE)
+        .read_to_end(&mut first_block)?;
+    Ok(is_binary(&first_block))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_nul_bytes_in_first_block() {
+        assert!(!is_binary(b"Are you nobody, too?\n"));
+        assert!(!is_binary(b""));
+        assert!(is_binary(b"\x7fELF\x02\x01\x01\x00"));
+        let mut late_nul = vec![b'a'; BINARY_SNIFF_SIZE as usize];
+        late_nul.push(0);
+        assert!(!is_binary(&late_nul));
+    }
+
+    #[test]
+    fn parse_binary_files() {
+        assert_eq!(
+            BinaryFiles::parse("without-match"),
+            Ok(BinaryFiles::WithoutMatch)
+        );
+        assert!(BinaryFiles::parse("hex").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use crate::arguments::{Argument, ARGUMENTS, GLOBAL_ARGUMENTS, SERVE_ARGUMENTS};
+use crate::config::Config;
+use crate::error::RunError;
+use crate::generate::Generate;
+use crate::serve::{self, ServeConfig};
+use crate::store::{self, RunConfig, SaveConfig, Store};
+use crate::{interactive, lsp};
+use colored::control;
+use std::env;
+use std::iter::{self, Peekable};
+
+/// Name and description of the commands. Without one of these names after the global options,
+/// the arguments are those of a search, e.g. `minigrep <query> <file_path>`.
+pub const COMMANDS: [(&str, &str); 6] = [
+    ("save", "Save the arguments of a search under a name"),
+    ("run", "Run a saved search, with more options"),
+    ("history", "Print the last searches"),
+    ("serve", "Serve the search as a local HTTP JSON service"),
+    ("lsp", "Speak the Language Server Protocol on stdio"),
+    (
+        "generate",
+        "Print a shell completion script or the man page",
+    ),
+];
+
+/// Options of the commands that have some, completed by the shells after the name of the command.
+pub const COMMAND_ARGUMENTS: [(&str, &[Argument]); 3] = [
+    ("save", &ARGUMENTS),
+    ("run", &ARGUMENTS),
+    ("serve", &SERVE_ARGUMENTS),
+];
+
+/// Options given before the command, see `GLOBAL_ARGUMENTS`.
+#[derive(Debug, Default, PartialEq)]
+pub struct GlobalOptions {
+    /// `Some(true)` for `--color always`, `Some(false)` for `--color never`, `None` to color
+    /// only the output of a terminal.
+    pub color: Option<bool>,
+    pub no_history: bool,
+}
+
+impl GlobalOptions {
+    /// Parses the global options at the start of `arguments`, up to the first other argument.
+    fn parse(
+        arguments: &mut Peekable<impl Iterator<Item = String>>,
+    ) -> Result<GlobalOptions, RunError> {
+        let usage_error =
+            || RunError::BadConfig("Usage: minigrep [options] [command] ...".to_string());
+        let mut options = GlobalOptions::default();
+        while let Some(definition) = arguments
+            .peek()
+            .and_then(|argument| Argument::find_in(&GLOBAL_ARGUMENTS, argument))
+        {
+            let argument = arguments.next().unwrap_or_default();
+            let value = definition
+                .value(&argument, arguments)
+                .ok_or_else(usage_error)?;
+            match definition.long {
+                "color" => {
+                    options.color = match value.as_str() {
+                        "auto" => None,
+                        "always" => Some(true),
+                        "never" => Some(false),
+                        _ => {
+                            return Err(RunError::BadConfig(format!(
+                                "Error: Unknown color mode `{value}`, expected one of: auto, always, never."
+                            )))
+                        }
+                    }
+                }
+                "no-history" => options.no_history = true,
+                long => unreachable!("`--{long}` is defined but not handled"),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A command with its own configuration.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// `arguments` are the arguments `config` is built from, recorded in the history.
+    Search {
+        config: Box<Config>,
+        arguments: Vec<String>,
+    },
+    Save(SaveConfig),
+    Run(RunConfig),
+    History,
+    Serve(ServeConfig),
+    Lsp,
+    Generate(Generate),
+}
+
+impl Command {
+    /// Parses `[global options] [command] <arguments of the command>`, without the program name.
+    ///
+    /// A command name is always the command, a query equal to a command name follows `--`, e.g.
+    /// `minigrep -- history notes.txt`.
+    pub fn parse(
+        arguments: impl Iterator<Item = String>,
+    ) -> Result<(GlobalOptions, Command), RunError> {
+        let mut arguments = arguments.peekable();
+        let options = GlobalOptions::parse(&mut arguments)?;
+        let name = arguments.next_if(|argument| COMMANDS.iter().any(|(name, _)| name == argument));
+        let arguments: Vec<String> = arguments.collect();
+        let Some(name) = name else {
+            return Ok((options, Command::search(arguments)?));
+        };
+        Ok((options, Command::build(&name, &arguments)?))
+    }
+
+    fn build(name: &str, arguments: &[String]) -> Result<Command, RunError> {
+        let usage_error = |usage: &str| RunError::BadConfig(format!("Usage: minigrep {usage}"));
+        let command = match (name, arguments) {
+            ("save", _) => Command::Save(SaveConfig::build(arguments.iter().cloned())?),
+            ("run", _) => Command::Run(RunConfig::build(arguments.iter().cloned())?),
+            ("serve", _) => Command::Serve(ServeConfig::build(arguments.iter().cloned())?),
+            ("history", []) => Command::History,
+            ("lsp", []) => Command::Lsp,
+            ("generate", [target]) => {
+                Command::Generate(Generate::parse(target).map_err(RunError::BadConfig)?)
+            }
+            ("generate", _) => return Err(usage_error("generate <bash|zsh|fish|man>")),
+            _ => return Err(usage_error(name)),
+        };
+        Ok(command)
+    }
+
+    fn search(arguments: Vec<String>) -> Result<Command, RunError> {
+        Ok(Command::Search {
+            config: Box::new(search_config(&arguments)?),
+            arguments,
+        })
+    }
+}
+
+fn search_config(arguments: &[String]) -> Result<Config, RunError> {
+    Config::build(iter::once(String::new()).chain(arguments.iter().cloned()))
+}
+
+/// Runs the command with the global options. Every error is returned, to be reported by `main`
+/// with its exit code.
+pub fn run(options: &GlobalOptions, command: Command) -> Result<(), RunError> {
+    if let Some(color) = options.color {
+        control::set_override(color);
+    }
+    match command {
+        Command::Search { config, arguments } => search(options, &config, &arguments),
+        Command::Save(save_config) => {
+            Store::open()?.save(&save_config.name, &save_config.arguments)?;
+            println!("Saved search `{}`.", save_config.name);
+            Ok(())
+        }
+        Command::Run(run_config) => {
+            let arguments = run_config.arguments(&Store::open()?)?;
+            search(options, &search_config(&arguments)?, &arguments)
+        }
+        Command::History => store::print_history(),
+        Command::Serve(serve_config) => serve::run(&serve_config),
+        Command::Lsp => lsp::run(),
+        Command::Generate(generate) => {
+            print!("{}", generate.generate());
+            Ok(())
+        }
+    }
+}
+
+fn search(options: &GlobalOptions, config: &Config, arguments: &[String]) -> Result<(), RunError> {
+    let is_recorded =
+        !options.no_history && env::var("MINIGREP_HISTORY").is_ok_and(|value| value == "true");
+    // The history is only a convenience, a search is not stopped when it cannot be recorded.
+    if is_recorded {
+        if let Err(error) = Store::open().and_then(|store| store.record(arguments)) {
+            eprintln!("Warning: The search is not recorded in the history: {error}");
+        }
+    }
+    if config.interactive {
+        interactive::run(config)
+    } else {
+        crate::run(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<(GlobalOptions, Command), RunError> {
+        Command::parse(arguments.iter().map(|argument| argument.to_string()))
+    }
+
+    #[test]
+    fn search_is_the_default_command() {
+        let (options, command) = parse(&["frog", "poem.txt"]).unwrap();
+        assert_eq!(options, GlobalOptions::default());
+        assert!(matches!(
+            command,
+            Command::Search { config, .. } if config.query == "frog" && config.file_path == "poem.txt"
+        ));
+
+        let (_, command) = parse(&["--", "serve", "poem.txt"]).unwrap();
+        assert!(matches!(command, Command::Search { config, .. } if config.query == "serve"));
+    }
+
+    #[test]
+    fn parse_global_options_and_commands() {
+        let (options, command) = parse(&["--color=never", "--no-history", "history"]).unwrap();
+        assert_eq!(
+            options,
+            GlobalOptions {
+                color: Some(false),
+                no_history: true
+            }
+        );
+        assert_eq!(command, Command::History);
+
+        let (options, command) = parse(&["--color", "always", "run", "todos", "--stats"]).unwrap();
+        assert_eq!(options.color, Some(true));
+        assert_eq!(
+            command,
+            Command::Run(RunConfig {
+                name: "todos".to_string(),
+                options: vec!["--stats".to_string()]
+            })
+        );
+
+        assert!(matches!(
+            parse(&["--color", "sometimes", "frog", "poem.txt"]),
+            Err(RunError::BadConfig(_))
+        ));
+        assert!(matches!(
+            parse(&["lsp", "--stdio"]),
+            Err(RunError::BadConfig(message)) if message == "Usage: minigrep lsp"
+        ));
+    }
+
+    #[test]
+    fn command_names_are_not_queries() {
+        assert!(matches!(
+            parse(&["history", "poem.txt"]),
+            Err(RunError::BadConfig(message)) if message == "Usage: minigrep history"
+        ));
+        let (_, command) = parse(&["--", "history", "poem.txt"]).unwrap();
+        assert!(matches!(
+            command,
+            Command::Search { config, .. } if config.query == "history" && config.file_path == "poem.txt"
+        ));
+        assert_eq!(
+            parse(&["generate", "fish"]).unwrap().1,
+            Command::Generate(Generate::Fish)
+        );
+        assert!(matches!(
+            parse(&["generate", "powershell"]),
+            Err(RunError::BadConfig(message))
+                if message == "Error: Cannot generate `powershell`, expected one of: bash, zsh, fish, man."
+        ));
+    }
+}
@@ -1,3 +1,16 @@
+use crate::arguments::{self, ParsedArgument, ARGUMENTS};
+use crate::binary::BinaryFiles;
+use crate::error::RunError;
+use crate::fields::Fields;
+use crate::file_types::FileTypes;
+use crate::format;
+use crate::json::{self, JsonFilter};
+use crate::limits::{self, LineRange};
+use crate::preprocess::Preprocessor;
+use crate::query::Query;
+use crate::scope::Scope;
+use crate::terminator::LineTerminator;
+use crate::walk::{Sort, SortKey};
 use std::env;
 
 #[derive(Debug, PartialEq)]
@@ -5,19 +18,203 @@ pub struct Config {
     pub query: String,
     pub file_path: String,
     pub ignore_case: bool,
+    pub file_types: FileTypes,
+    pub interactive: bool,
+    pub no_messages: bool,
+    pub mmap: Option<bool>,
+    pub preprocessor: Option<Preprocessor>,
+    pub binary_files: BinaryFiles,
+    /// Search each member of the archives as a file named `archive!/member`.
+    pub search_archives: bool,
+    pub line_terminator: LineTerminator,
+    /// Follow the file paths by NUL instead of `:`, for `xargs -0`.
+    pub null: bool,
+    /// Only search this part of Rust source code, the rest of the lines is masked.
+    pub scope: Option<Scope>,
+    pub line_range: Option<LineRange>,
+    pub max_columns: Option<usize>,
+    pub only_matching: bool,
+    /// Print every line, not only the matching ones.
+    pub passthru: bool,
+    pub format: Option<String>,
+    pub fields: Option<Fields>,
+    pub json: Option<JsonFilter>,
+    pub boolean_query: Option<Query>,
+    /// `file_path` is a unified diff (`-` for stdin) whose added and removed lines are searched.
+    pub diff: bool,
+    pub stats: bool,
+    pub sort: Option<Sort>,
+    pub unique: bool,
 }
 
 impl Config {
-    pub fn build(mut arguments: impl Iterator<Item = String>) -> Result<Config, &'static str> {
+    pub fn build(mut arguments: impl Iterator<Item = String>) -> Result<Config, RunError> {
         arguments.next();
         let error_message = "Usage: minigrep <query> <file_path>";
-        let query = match arguments.next() {
-            Some(arg) => arg,
-            None => return Err(error_message),
+        let usage_error = || RunError::BadConfig(error_message.to_string());
+        let config_error = |message: &str| RunError::BadConfig(message.to_string());
+        let mut positional_arguments: Vec<String> = Vec::new();
+        let mut file_types = FileTypes::default();
+        let mut interactive = false;
+        let mut no_messages = false;
+        let mut mmap = None;
+        let mut preprocessor_command = None;
+        let mut preprocessor_globs: Vec<String> = Vec::new();
+        let mut binary_files = BinaryFiles::default();
+        let mut search_archives = false;
+        let mut crlf = false;
+        let mut null_data = false;
+        let mut null = false;
+        let mut scope = None;
+        let mut line_range = None;
+        let mut max_columns = None;
+        let mut only_matching = false;
+        let mut passthru = false;
+        let mut format = None;
+        let mut field_indexes: Vec<usize> = Vec::new();
+        let mut delimiter = None;
+        let mut json: Option<JsonFilter> = None;
+        let mut is_boolean = false;
+        let mut diff_path = None;
+        let mut stats = false;
+        let mut sort = None;
+        let mut unique = false;
+        for parsed_argument in arguments::parse(&ARGUMENTS, arguments, usage_error)? {
+            let (definition, value) = match parsed_argument {
+                ParsedArgument::Option(definition, value) => (definition, value),
+                ParsedArgument::Positional(argument) => {
+                    positional_arguments.push(argument);
+                    continue;
+                }
+            };
+            match definition.long {
+                "type" => file_types.selected.push(value),
+                "type-not" => file_types.negated.push(value),
+                "type-add" => file_types.add(&value).map_err(config_error)?,
+                "interactive" => interactive = true,
+                "no-messages" => no_messages = true,
+                "mmap" => mmap = Some(true),
+                "no-mmap" => mmap = Some(false),
+                "pre" => preprocessor_command = Some(value),
+                "pre-glob" => preprocessor_globs.push(value),
+                "text" => binary_files = BinaryFiles::Text,
+                "search-archives" => search_archives = true,
+                "crlf" => crlf = true,
+                "null-data" => null_data = true,
+                "null" => null = true,
+                "binary-files" => {
+                    binary_files = BinaryFiles::parse(&value).map_err(RunError::BadConfig)?;
+                }
+                "scope" => scope = Some(Scope::parse(&value).map_err(RunError::BadConfig)?),
+                "lines" => {
+                    line_range = Some(LineRange::parse(&value).map_err(RunError::BadConfig)?)
+                }
+                "max-columns" => {
+                    max_columns =
+                        Some(limits::parse_max_columns(&value).map_err(RunError::BadConfig)?);
+                }
+                "only-matching" => only_matching = true,
+                "passthru" => passthru = true,
+                "format" => {
+                    format::validate(&value).map_err(RunError::BadConfig)?;
+                    format = Some(value);
+                }
+                "field" => {
+                    field_indexes
+                        .extend(Fields::parse_indexes(&value).map_err(RunError::BadConfig)?);
+                }
+                "delimiter" => {
+                    delimiter = Some(Fields::parse_delimiter(&value).map_err(RunError::BadConfig)?);
+                }
+                "json-path" => {
+                    let path = json::parse_path(&value).map_err(RunError::BadConfig)?;
+                    json.get_or_insert_with(JsonFilter::default).path = Some(path);
+                }
+                "where" => {
+                    let predicate = json::parse_predicate(&value).map_err(RunError::BadConfig)?;
+                    json.get_or_insert_with(JsonFilter::default)
+                        .predicates
+                        .push(predicate);
+                }
+                "boolean" => is_boolean = true,
+                "stats" => stats = true,
+                "sort" | "sortr" => {
+                    sort = Some(Sort {
+                        key: SortKey::parse(&value).map_err(RunError::BadConfig)?,
+                        is_reversed: definition.long == "sortr",
+                    });
+                }
+                "unique" => unique = true,
+                "diff" => diff_path = Some(value),
+                long => unreachable!("`--{long}` is defined but not handled"),
+            }
+        }
+        file_types.validate().map_err(config_error)?;
+        let fields = match (field_indexes.is_empty(), delimiter) {
+            (true, None) => None,
+            (true, Some(_)) => {
+                return Err(config_error("Error: `--delimiter` requires `--field`."))
+            }
+            (false, delimiter) => Some(Fields {
+                indexes: field_indexes,
+                delimiter: delimiter.unwrap_or(','),
+            }),
+        };
+        let preprocessor = match (preprocessor_command, preprocessor_globs.is_empty()) {
+            (None, true) => None,
+            (None, false) => return Err(config_error("Error: `--pre-glob` requires `--pre`.")),
+            (Some(command), _) => Some(Preprocessor {
+                command,
+                globs: preprocessor_globs,
+            }),
+        };
+        let line_terminator = match (crlf, null_data) {
+            (false, false) => LineTerminator::Lf,
+            (true, false) => LineTerminator::Crlf,
+            (false, true) => LineTerminator::Nul,
+            (true, true) => {
+                return Err(config_error(
+                    "Error: `--crlf` cannot be used with `--null-data`.",
+                ))
+            }
         };
-        let file_path = match arguments.next() {
+        let mut positional_arguments = positional_arguments.into_iter();
+        let query = match positional_arguments.next() {
             Some(arg) => arg,
-            None => return Err(error_message),
+            None => return Err(usage_error()),
+        };
+        let diff = diff_path.is_some();
+        let file_path = match (positional_arguments.next(), diff_path) {
+            (None, Some(diff_path)) => diff_path,
+            (Some(arg), None) => arg,
+            (None, None) if passthru => "-".to_string(),
+            _ => return Err(usage_error()),
+        };
+        if diff && (interactive || fields.is_some()) {
+            return Err(config_error(
+                "Error: `--diff` cannot be used with `--interactive` or `--field`.",
+            ));
+        }
+        if passthru && (only_matching || format.is_some()) {
+            return Err(config_error(
+                "Error: `--passthru` cannot be used with `--only-matching` or `--format`.",
+            ));
+        }
+        if query.is_empty() && !interactive {
+            return Err(RunError::BadPattern {
+                pattern: query,
+                reason: "the query should not be empty".to_string(),
+            });
+        }
+        let boolean_query = if is_boolean {
+            if fields.is_some() || json.is_some() {
+                return Err(config_error(
+                    "Error: `--boolean` cannot be used with `--field`, `--json-path` or `--where`.",
+                ));
+            }
+            Some(Query::parse(&query)?)
+        } else {
+            None
         };
         let ignore_case = match env::var("IGNORE_CASE") {
             Ok(value) => value == "true",
@@ -27,6 +224,28 @@ impl Config {
             query,
             file_path,
             ignore_case,
+            file_types,
+            interactive,
+            no_messages,
+            mmap,
+            preprocessor,
+            binary_files,
+            search_archives,
+            line_terminator,
+            null,
+            scope,
+            line_range,
+            max_columns,
+            only_matching,
+            passthru,
+            format,
+            fields,
+            json,
+            boolean_query,
+            diff,
+            stats,
+            sort,
+            unique,
         })
     }
 }
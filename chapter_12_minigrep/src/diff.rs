@@ -0,0 +1,171 @@
+use crate::reader::FileLines;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DiffLineKind {
+    Added,
+    Removed,
+}
+
+impl DiffLineKind {
+    pub fn marker(&self) -> char {
+        match self {
+            DiffLineKind::Added => '+',
+            DiffLineKind::Removed => '-',
+        }
+    }
+}
+
+/// Added and removed lines of one file of a unified diff.
+#[derive(Debug, PartialEq)]
+pub struct DiffFile {
+    /// `line_indexes` are the 0-based post-image line numbers: the line of an added line in the
+    /// new file, or the position in the new file where a removed line was.
+    pub file_lines: FileLines,
+    pub kinds: Vec<DiffLineKind>,
+}
+
+/// Removes the `a/`/`b/` prefixes of git and the timestamps of `diff -u` from a header path.
+fn header_path(header: &str) -> &str {
+    let path = header.split('\t').next().unwrap_or(header).trim_end();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+}
+
+/// Parses `@@ -old_start,old_count +new_start,new_count @@`, the counts default to 1.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let mut ranges = line.strip_prefix("@@ ")?.split(' ');
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (_, old_count) = parse_range(ranges.next()?.strip_prefix('-')?)?;
+    let (new_start, new_count) = parse_range(ranges.next()?.strip_prefix('+')?)?;
+    Some((old_count, new_start, new_count))
+}
+
+pub fn parse(diff: &str) -> Vec<DiffFile> {
+    let mut diff_files: Vec<DiffFile> = Vec::new();
+    let mut old_path = "";
+    let (mut old_remaining, mut new_remaining, mut new_line_index) = (0, 0, 0);
+    for line in diff.lines() {
+        let is_in_hunk = old_remaining > 0 || new_remaining > 0;
+        if !is_in_hunk {
+            if let Some(path) = line.strip_prefix("--- ") {
+                old_path = header_path(path);
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                let path = match header_path(path) {
+                    "/dev/null" => old_path,
+                    new_path => new_path,
+                };
+                diff_files.push(DiffFile {
+                    file_lines: FileLines {
+                        file_path: PathBuf::from(path),
+                        line_indexes: Vec::new(),
+                        lines: Vec::new(),
+                        is_binary: false,
+                        line_terminators: Vec::new(),
+                    },
+                    kinds: Vec::new(),
+                });
+            } else if let Some((old_count, new_start, new_count)) = parse_hunk_header(line) {
+                old_remaining = old_count;
+                new_remaining = new_count;
+                new_line_index = new_start.saturating_sub(1);
+            }
+            continue;
+        }
+        let Some(diff_file) = diff_files.last_mut() else {
+            continue;
+        };
+        let kind = match line.chars().next() {
+            Some('+') => {
+                new_remaining = new_remaining.saturating_sub(1);
+                new_line_index += 1;
+                DiffLineKind::Added
+            }
+            Some('-') => {
+                old_remaining = old_remaining.saturating_sub(1);
+                DiffLineKind::Removed
+            }
+            Some('\\') => continue,
+            _ => {
+                old_remaining = old_remaining.saturating_sub(1);
+                new_remaining = new_remaining.saturating_sub(1);
+                new_line_index += 1;
+                continue;
+            }
+        };
+        let line_index = match kind {
+            DiffLineKind::Added => new_line_index - 1,
+            DiffLineKind::Removed => new_line_index,
+        };
+        diff_file.file_lines.line_indexes.push(line_index);
+        diff_file.file_lines.lines.push(line[1..].to_string());
+        diff_file.file_lines.line_terminators.push("\n");
+        diff_file.kinds.push(kind);
+    }
+    diff_files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_unified_diff() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,4 @@
+ fn main() {
+--- old comment
++    println!(\"new\");
+     run();
+ }
+@@ -10 +10,2 @@
+ last
++added
+--- a/removed.txt\t2023-01-01 00:00:00
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+\\ No newline at end of file
+";
+        let diff_files = parse(diff);
+        assert_eq!(diff_files.len(), 2);
+        let file_lines = &diff_files[0].file_lines;
+        assert_eq!(file_lines.file_path, PathBuf::from("src/lib.rs"));
+        assert_eq!(
+            file_lines.lines,
+            vec!["-- old comment", "    println!(\"new\");", "added"]
+        );
+        assert_eq!(file_lines.line_indexes, vec![1, 1, 10]);
+        assert_eq!(
+            diff_files[0].kinds,
+            vec![
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+                DiffLineKind::Added
+            ]
+        );
+        assert_eq!(
+            diff_files[1].file_lines.file_path,
+            PathBuf::from("removed.txt")
+        );
+        assert_eq!(diff_files[1].file_lines.lines, vec!["gone"]);
+    }
+    #[test]
+    fn parse_hunk_longer_than_its_header() {
+        let diff =
+            "--- a/x\n+++ b/x\n@@ -0,0 +1,1 @@\n-x\n+y\n+z\n--- a/w\n+++ b/w\n@@ -1 +1 @@\n+w\n";
+        let diff_files = parse(diff);
+        assert_eq!(diff_files.len(), 2);
+        assert_eq!(diff_files[0].file_lines.lines, vec!["x", "y"]);
+        assert_eq!(diff_files[1].file_lines.lines, vec!["w"]);
+    }
+}
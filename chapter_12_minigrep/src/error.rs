@@ -1,15 +1,83 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 pub enum RunError {
-    InputOutputError(std::io::Error),
+    FileNotFound { path: PathBuf, source: io::Error },
+    PermissionDenied { path: PathBuf, source: io::Error },
+    IsADirectory { path: PathBuf, source: io::Error },
+    InvalidUtf8 { path: PathBuf, source: io::Error },
+    BadPattern { pattern: String, reason: String },
+    BadConfig(String),
+    InputOutputError(io::Error),
     Other(Box<dyn Error>),
 }
 
+impl RunError {
+    pub fn from_io_error(error: io::Error, path: &Path) -> Self {
+        let path = path.to_path_buf();
+        match error.kind() {
+            ErrorKind::NotFound => RunError::FileNotFound {
+                path,
+                source: error,
+            },
+            ErrorKind::PermissionDenied => RunError::PermissionDenied {
+                path,
+                source: error,
+            },
+            ErrorKind::IsADirectory => RunError::IsADirectory {
+                path,
+                source: error,
+            },
+            ErrorKind::InvalidData => RunError::InvalidUtf8 {
+                path,
+                source: error,
+            },
+            _ => RunError::InputOutputError(error),
+        }
+    }
+
+    /// Exit codes follow the BSD `sysexits.h` conventions:
+    ///
+    /// - `64` (`EX_USAGE`): invalid command line arguments.
+    /// - `65` (`EX_DATAERR`): invalid pattern, or file content that is not valid UTF-8.
+    /// - `66` (`EX_NOINPUT`): file not found, or a directory where a file was expected.
+    /// - `74` (`EX_IOERR`): any other input/output error.
+    /// - `77` (`EX_NOPERM`): permission denied.
+    /// - `1`: any other error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RunError::BadConfig(_) => 64,
+            RunError::BadPattern { .. } | RunError::InvalidUtf8 { .. } => 65,
+            RunError::FileNotFound { .. } | RunError::IsADirectory { .. } => 66,
+            RunError::InputOutputError(_) => 74,
+            RunError::PermissionDenied { .. } => 77,
+            RunError::Other(_) => 1,
+        }
+    }
+}
+
 impl Display for RunError {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            RunError::FileNotFound { path, .. } => {
+                write!(formatter, "File `{}` not found.", path.display())
+            }
+            RunError::PermissionDenied { path, .. } => {
+                write!(formatter, "Permission denied to read `{}`.", path.display())
+            }
+            RunError::IsADirectory { path, .. } => {
+                write!(formatter, "`{}` is a directory.", path.display())
+            }
+            RunError::InvalidUtf8 { path, .. } => {
+                write!(formatter, "File `{}` is not valid UTF-8.", path.display())
+            }
+            RunError::BadPattern { pattern, reason } => {
+                write!(formatter, "Invalid pattern `{pattern}`: {reason}.")
+            }
+            RunError::BadConfig(message) => write!(formatter, "{message}"),
             RunError::InputOutputError(error) => {
                 write!(formatter, "{error}")
             }
@@ -18,10 +86,46 @@ impl Display for RunError {
     }
 }
 
-impl Error for RunError {}
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RunError::FileNotFound { source, .. }
+            | RunError::PermissionDenied { source, .. }
+            | RunError::IsADirectory { source, .. }
+            | RunError::InvalidUtf8 { source, .. } => Some(source),
+            RunError::InputOutputError(error) => Some(error),
+            RunError::Other(error) => Some(error.as_ref()),
+            RunError::BadPattern { .. } | RunError::BadConfig(_) => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for RunError {
     fn from(error: std::io::Error) -> Self {
         RunError::InputOutputError(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_io_error_keeps_path_and_source() {
+        let path = Path::new("poem.txt");
+        let error = RunError::from_io_error(io::Error::from(ErrorKind::NotFound), path);
+        assert!(
+            matches!(&error, RunError::FileNotFound { path, .. } if path == Path::new("poem.txt"))
+        );
+        assert_eq!(error.to_string(), "File `poem.txt` not found.");
+        assert_eq!(error.exit_code(), 66);
+        assert!(error.source().is_some());
+
+        let error = RunError::from_io_error(io::Error::from(ErrorKind::PermissionDenied), path);
+        assert!(matches!(error, RunError::PermissionDenied { .. }));
+        assert_eq!(error.exit_code(), 77);
+
+        let error = RunError::from_io_error(io::Error::from(ErrorKind::InvalidData), path);
+        assert!(matches!(error, RunError::InvalidUtf8 { .. }));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::reader::FileLines;
+use crate::search::MatchingItem;
+
+#[derive(Debug, PartialEq)]
+pub struct Fields {
+    /// 0-based indexes of the fields to search in.
+    pub indexes: Vec<usize>,
+    pub delimiter: char,
+}
+
+impl Fields {
+    /// Parses a `--field` value: 1-based field numbers separated by commas, e.g. `1,3`.
+    pub fn parse_indexes(value: &str) -> Result<Vec<usize>, String> {
+        value
+            .split(',')
+            .map(|number| match number.trim().parse::<usize>() {
+                Ok(number) if number > 0 => Ok(number - 1),
+                _ => Err(format!(
+                    "Error: Invalid field `{number}`, fields are numbered from 1."
+                )),
+            })
+            .collect()
+    }
+
+    pub fn parse_delimiter(value: &str) -> Result<char, String> {
+        let mut characters = value.chars();
+        match (value, characters.next(), characters.next()) {
+            ("\\t" | "tab", _, _) => Ok('\t'),
+            (_, Some(character), None) if character != '"' => Ok(character),
+            _ => Err(format!(
+                "Error: Invalid delimiter `{value}`, expected a single character."
+            )),
+        }
+    }
+
+    /// Removes the matches that are not inside one of the selected fields, or that are in the
+    /// header (the first record).
+    pub fn filter_matching_items(
+        &self,
+        records: &[String],
+        matching_items: Vec<MatchingItem>,
+    ) -> Vec<MatchingItem> {
+        matching_items
+            .into_iter()
+            .filter(|item| {
+                item.line_index > 0
+                    && field_ranges(&records[item.line_index], self.delimiter)
+                        .iter()
+                        .enumerate()
+                        .any(|(field_index, (start_index, end_index))| {
+                            self.indexes.contains(&field_index)
+                                && item.start_index >= *start_index
+                                && item.end_index < *end_index
+                        })
+            })
+            .collect()
+    }
+}
+
+/// Joins the lines into records, a quoted field may contain line breaks (RFC 4180). A record is
+/// printed with the terminator of its last line.
+pub fn records(file_lines: FileLines) -> FileLines {
+    let mut line_indexes = Vec::new();
+    let mut records: Vec<String> = Vec::new();
+    let mut line_terminators = Vec::new();
+    let mut is_complete = true;
+    let lines = file_lines
+        .line_indexes
+        .into_iter()
+        .zip(file_lines.lines)
+        .zip(file_lines.line_terminators);
+    for ((line_index, line), line_terminator) in lines {
+        let quotes_count = line.matches('"').count();
+        if is_complete {
+            line_indexes.push(line_index);
+            records.push(line);
+            line_terminators.push(line_terminator);
+        } else if let (Some(record), Some(record_terminator)) =
+            (records.last_mut(), line_terminators.last_mut())
+        {
+            record.push('\n');
+            record.push_str(&line);
+            *record_terminator = line_terminator;
+        }
+        if quotes_count % 2 == 1 {
+            is_complete = !is_complete;
+        }
+    }
+    FileLines {
+        file_path: file_lines.file_path,
+        line_indexes,
+        lines: records,
+        is_binary: file_lines.is_binary,
+        line_terminators,
+    }
+}
+
+/// Byte ranges (`start..end`) of the content of each field of the record, without the
+/// surrounding quotes of quoted fields.
+pub fn field_ranges(record: &str, delimiter: char) -> Vec<(usize, usize)> {
+    let unquote = |start_index: usize, end_index: usize| {
+        let field = &record[start_index..end_index];
+        if field.len() >= 2 && field.starts_with('"') && field.ends_with('"') {
+            (start_index + 1, end_index - 1)
+        } else {
+            (start_index, end_index)
+        }
+    };
+    let mut ranges = Vec::new();
+    let mut start_index = 0;
+    let mut is_quoted = false;
+    for (index, character) in record.char_indices() {
+        if character == '"' {
+            is_quoted = !is_quoted;
+        } else if character == delimiter && !is_quoted {
+            ranges.push(unquote(start_index, index));
+            start_index = index + character.len_utf8();
+        }
+    }
+    ranges.push(unquote(start_index, record.len()));
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn quoted_field_ranges() {
+        let record = r#"1,"Doe, John","He said ""hi""",x"#;
+        let fields: Vec<&str> = field_ranges(record, ',')
+            .into_iter()
+            .map(|(start_index, end_index)| &record[start_index..end_index])
+            .collect();
+        assert_eq!(fields, vec!["1", "Doe, John", r#"He said ""hi"""#, "x"]);
+        assert_eq!(field_ranges("a\t\tb", '\t'), vec![(0, 1), (2, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn multiline_records() {
+        let file_lines = FileLines {
+            file_path: PathBuf::from("people.csv"),
+            line_indexes: vec![0, 1, 2, 3],
+            lines: vec![
+                "id,note".to_string(),
+                "1,\"first".to_string(),
+                "second\"".to_string(),
+                "2,third".to_string(),
+            ],
+            is_binary: false,
+            line_terminators: vec!["\n", "\n", "\r\n", "\n"],
+        };
+        let records = records(file_lines);
+        assert_eq!(records.line_indexes, vec![0, 1, 3]);
+        assert_eq!(records.lines[1], "1,\"first\nsecond\"");
+        assert_eq!(records.line_terminators, vec!["\n", "\r\n", "\n"]);
+    }
+
+    #[test]
+    fn filter_matching_items_by_field() {
+        let fields = Fields {
+            indexes: vec![1],
+            delimiter: ',',
+        };
+        let records = ["name,city".to_string(), "Paris,Paris".to_string()];
+        let matching_items = vec![
+            MatchingItem {
+                start_index: 0,
+                end_index: 4,
+                line_index: 1,
+            },
+            MatchingItem {
+                start_index: 6,
+                end_index: 10,
+                line_index: 1,
+            },
+        ];
+        assert_eq!(
+            fields.filter_matching_items(&records, matching_items),
+            vec![MatchingItem {
+                start_index: 6,
+                end_index: 10,
+                line_index: 1,
+            }]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+#[derive(Debug, PartialEq)]
+pub struct FileType {
+    pub name: String,
+    pub globs: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FileTypes {
+    pub definitions: Vec<FileType>,
+    pub selected: Vec<String>,
+    pub negated: Vec<String>,
+}
+
+const DEFAULT_FILE_TYPES: [(&str, &[&str]); 8] = [
+    ("json", &["*.json"]),
+    ("lock", &["*.lock"]),
+    ("markdown", &["*.md", "*.markdown"]),
+    ("rust", &["*.rs"]),
+    ("shell", &["*.sh", "*.bash", "*.zsh"]),
+    ("text", &["*.txt"]),
+    ("toml", &["*.toml"]),
+    ("yaml", &["*.yml", "*.yaml"]),
+];
+
+impl Default for FileTypes {
+    fn default() -> Self {
+        let definitions = DEFAULT_FILE_TYPES
+            .iter()
+            .map(|(name, globs)| FileType {
+                name: name.to_string(),
+                globs: globs.iter().map(|glob| glob.to_string()).collect(),
+            })
+            .collect();
+        FileTypes {
+            definitions,
+            selected: Vec::new(),
+            negated: Vec::new(),
+        }
+    }
+}
+
+impl FileTypes {
+    /// Adds a glob to a (possibly new) file type, from a `name:glob` definition.
+    pub fn add(&mut self, definition: &str) -> Result<(), &'static str> {
+        let (name, glob) = match definition.split_once(':') {
+            Some((name, glob)) if !name.is_empty() && !glob.is_empty() => (name, glob),
+            _ => return Err("Error: `--type-add` expects a `name:glob` definition."),
+        };
+        match self
+            .definitions
+            .iter_mut()
+            .find(|file_type| file_type.name == name)
+        {
+            Some(file_type) => file_type.globs.push(glob.to_string()),
+            None => self.definitions.push(FileType {
+                name: name.to_string(),
+                globs: vec![glob.to_string()],
+            }),
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let is_known = |name: &String| {
+            self.definitions
+                .iter()
+                .any(|file_type| &file_type.name == name)
+        };
+        if self.selected.iter().chain(&self.negated).all(is_known) {
+            Ok(())
+        } else {
+            Err("Error: Unknown file type.")
+        }
+    }
+
+    pub fn is_match(&self, file_name: &str) -> bool {
+        let matches_any = |names: &[String]| {
+            self.definitions
+                .iter()
+                .filter(|file_type| names.contains(&file_type.name))
+                .flat_map(|file_type| &file_type.globs)
+                .any(|glob| glob_matches(glob, file_name))
+        };
+        if !self.selected.is_empty() && !matches_any(&self.selected) {
+            return false;
+        }
+        !matches_any(&self.negated)
+    }
+}
+
+pub fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut glob_index, mut text_index) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while text_index < text.len() {
+        match glob.get(glob_index) {
+            Some('*') => {
+                backtrack = Some((glob_index, text_index));
+                glob_index += 1;
+            }
+            Some(character) if *character == '?' || *character == text[text_index] => {
+                glob_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_text_index)) => {
+                    glob_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                    backtrack = Some((star_index, star_text_index + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[glob_index..].iter().all(|character| *character == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(glob_matches("Cargo.*", "Cargo.lock"));
+        assert!(glob_matches("?.txt", "a.txt"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("*.rs", "main.rs.bak"));
+        assert!(!glob_matches("?.txt", "ab.txt"));
+    }
+
+    #[test]
+    fn selected_and_negated_types() {
+        let mut file_types = FileTypes::default();
+        file_types.add("web:*.html").unwrap();
+        file_types.selected.push("rust".to_string());
+        file_types.selected.push("web".to_string());
+        assert!(file_types.validate().is_ok());
+        assert!(file_types.is_match("lib.rs"));
+        assert!(file_types.is_match("index.html"));
+        assert!(!file_types.is_match("README.md"));
+
+        let mut file_types = FileTypes::default();
+        file_types.negated.push("lock".to_string());
+        assert!(file_types.is_match("README.md"));
+        assert!(!file_types.is_match("Cargo.lock"));
+
+        file_types.negated.push("unknown".to_string());
+        assert!(file_types.validate().is_err());
+    }
+}
@@ -0,0 +1,93 @@
+pub const PLACEHOLDERS: [&str; 5] = ["path", "line", "col", "match", "text"];
+
+#[derive(Debug, PartialEq)]
+pub struct FormatValues<'a> {
+    pub path: &'a str,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based byte column of the match in the line.
+    pub col: usize,
+    pub matching_text: &'a str,
+    pub text: &'a str,
+}
+
+/// Checks that every `{placeholder}` of the `--format` template is known.
+pub fn validate(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start_index) = rest.find('{') {
+        let end_index = match rest[start_index..].find('}') {
+            Some(end_index) => start_index + end_index,
+            None => return Err("Error: Unclosed `{` in `--format` template.".to_string()),
+        };
+        let placeholder = &rest[start_index + 1..end_index];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!(
+                "Error: Unknown placeholder `{{{placeholder}}}` in `--format` template (expected one of: {}).",
+                PLACEHOLDERS.map(|placeholder| format!("{{{placeholder}}}")).join(", ")
+            ));
+        }
+        rest = &rest[end_index + 1..];
+    }
+    Ok(())
+}
+
+pub fn render(template: &str, values: &FormatValues) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start_index) = rest.find('{') {
+        output.push_str(&rest[..start_index]);
+        rest = &rest[start_index..];
+        let end_index = match rest.find('}') {
+            Some(end_index) => end_index,
+            None => break,
+        };
+        match &rest[1..end_index] {
+            "path" => output.push_str(values.path),
+            "line" => output.push_str(&values.line.to_string()),
+            "col" => output.push_str(&values.col.to_string()),
+            "match" => output.push_str(values.matching_text),
+            "text" => output.push_str(values.text),
+            _ => output.push_str(&rest[..=end_index]),
+        }
+        rest = &rest[end_index + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_template() {
+        let values = FormatValues {
+            path: "poem.txt",
+            line: 3,
+            col: 6,
+            matching_text: "there",
+            text: "Then there's a pair of us - don't tell!",
+        };
+        assert_eq!(
+            render("{path}:{line}:{col}:{match}", &values),
+            "poem.txt:3:6:there"
+        );
+        assert_eq!(
+            render("{match}\t{text}", &values),
+            format!("there\t{}", values.text)
+        );
+        let values = FormatValues {
+            path: "{match}.txt",
+            ..values
+        };
+        assert_eq!(render("{path}", &values), "{match}.txt");
+    }
+
+    #[test]
+    fn validate_template() {
+        assert!(validate("{path}:{line}:{col}:{match}").is_ok());
+        assert!(validate("no placeholders").is_ok());
+        assert!(validate("{file}").is_err());
+        assert!(validate("{path").is_err());
+    }
+}
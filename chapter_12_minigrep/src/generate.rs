@@ -0,0 +1,335 @@
+use crate::arguments::{Argument, ArgumentValue, ARGUMENTS, GLOBAL_ARGUMENTS};
+use crate::command::{COMMANDS, COMMAND_ARGUMENTS};
+use crate::file_types::FileTypes;
+
+/// Values of `minigrep generate`, completed by the shells.
+const TARGETS: [&str; 4] = ["bash", "zsh", "fish", "man"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Generate {
+    Bash,
+    Zsh,
+    Fish,
+    Man,
+}
+
+impl Generate {
+    pub fn parse(value: &str) -> Result<Generate, String> {
+        match value {
+            "bash" => Ok(Generate::Bash),
+            "zsh" => Ok(Generate::Zsh),
+            "fish" => Ok(Generate::Fish),
+            "man" => Ok(Generate::Man),
+            _ => Err(format!(
+                "Error: Cannot generate `{value}`, expected one of: bash, zsh, fish, man."
+            )),
+        }
+    }
+
+    pub fn generate(&self) -> String {
+        match self {
+            Generate::Bash => bash(),
+            Generate::Zsh => zsh(),
+            Generate::Fish => fish(),
+            Generate::Man => man(),
+        }
+    }
+}
+
+fn file_type_names() -> Vec<String> {
+    FileTypes::default()
+        .definitions
+        .into_iter()
+        .map(|file_type| file_type.name)
+        .collect()
+}
+
+/// The options of a search, the global options and the options of the commands, once each.
+fn all_arguments() -> Vec<&'static Argument> {
+    let mut arguments: Vec<&Argument> = ARGUMENTS.iter().chain(&GLOBAL_ARGUMENTS).collect();
+    for argument in COMMAND_ARGUMENTS
+        .iter()
+        .flat_map(|(_, command_arguments)| command_arguments.iter())
+    {
+        if !arguments.contains(&argument) {
+            arguments.push(argument);
+        }
+    }
+    arguments
+}
+
+/// The commands with `argument` among their options, empty for the options of a search and the
+/// global options, given with any command.
+fn commands_of(argument: &Argument) -> Vec<&'static str> {
+    if ARGUMENTS.contains(argument) || GLOBAL_ARGUMENTS.contains(argument) {
+        return Vec::new();
+    }
+    COMMAND_ARGUMENTS
+        .iter()
+        .filter(|(_, command_arguments)| command_arguments.contains(argument))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+fn command_names() -> Vec<&'static str> {
+    COMMANDS.iter().map(|(name, _)| *name).collect()
+}
+
+fn flags(argument: &Argument) -> Vec<String> {
+    let mut flags = Vec::new();
+    if let Some(short) = argument.short {
+        flags.push(format!("-{short}"));
+    }
+    flags.push(format!("--{}", argument.long));
+    flags
+}
+
+fn bash() -> String {
+    let search_flags: Vec<String> = ARGUMENTS
+        .iter()
+        .chain(&GLOBAL_ARGUMENTS)
+        .flat_map(flags)
+        .collect();
+    let mut script = String::from(
+        "_minigrep() {\n    local current=\"${COMP_WORDS[COMP_CWORD]}\"\n    local previous=\"${COMP_WORDS[COMP_CWORD-1]}\"\n    case \"$previous\" in\n",
+    );
+    for argument in all_arguments() {
+        let completion = match argument.value {
+            ArgumentValue::None => continue,
+            ArgumentValue::FileType => {
+                format!(
+                    "COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))",
+                    file_type_names().join(" ")
+                )
+            }
+            ArgumentValue::Path => "COMPREPLY=($(compgen -f -- \"$current\"))".to_string(),
+            ArgumentValue::Directory => "COMPREPLY=($(compgen -d -- \"$current\"))".to_string(),
+            ArgumentValue::Choices(_, choices) => {
+                format!(
+                    "COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))",
+                    choices.join(" ")
+                )
+            }
+            ArgumentValue::Text(_) => "COMPREPLY=()".to_string(),
+        };
+        script.push_str(&format!(
+            "        {})\n            {completion}\n            return\n            ;;\n",
+            flags(argument).join("|")
+        ));
+    }
+    script.push_str(&format!(
+        "        generate)\n            COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))\n            return\n            ;;\n",
+        TARGETS.join(" ")
+    ));
+    // The options completed after `-` are those of the command named by the first argument.
+    script.push_str(
+        "    esac\n    if [[ \"$current\" == -* ]]; then\n        case \"${COMP_WORDS[1]}\" in\n",
+    );
+    for (name, arguments) in COMMAND_ARGUMENTS {
+        let command_flags: Vec<String> = arguments.iter().flat_map(flags).collect();
+        script.push_str(&format!(
+            "            {name})\n                COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))\n                ;;\n",
+            command_flags.join(" ")
+        ));
+    }
+    script.push_str(&format!(
+        "            *)\n                COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))\n                ;;\n        esac\n    elif [[ $COMP_CWORD -eq 1 ]]; then\n        COMPREPLY=($(compgen -W \"{}\" -- \"$current\"))\n    else\n        COMPREPLY=($(compgen -f -- \"$current\"))\n    fi\n}}\n\ncomplete -o filenames -F _minigrep minigrep\n",
+        search_flags.join(" "),
+        command_names().join(" ")
+    ));
+    script
+}
+
+fn zsh() -> String {
+    let escape = |text: &str| {
+        text.replace('\'', "'\\''")
+            .replace('[', "\\[")
+            .replace(']', "\\]")
+            .replace(':', "\\:")
+    };
+    let mut script = String::from("#compdef minigrep\n\n_minigrep() {\n    _arguments -s \\\n");
+    for argument in all_arguments() {
+        let specification = match argument.short {
+            Some(short) => format!("'*'{{-{short},--{}}}'", argument.long),
+            None => format!("'*--{}", argument.long),
+        };
+        let action = match argument.value {
+            ArgumentValue::None => String::new(),
+            ArgumentValue::FileType => format!(":TYPE:({})", file_type_names().join(" ")),
+            ArgumentValue::Path => ":FILE:_files".to_string(),
+            ArgumentValue::Directory => ":DIRECTORY:_files -/".to_string(),
+            ArgumentValue::Choices(name, choices) => format!(":{name}:({})", choices.join(" ")),
+            ArgumentValue::Text(name) => format!(":{}: ", escape(name)),
+        };
+        script.push_str(&format!(
+            "        {specification}[{}]{action}' \\\n",
+            escape(argument.help)
+        ));
+    }
+    script.push_str(&format!(
+        "        '1:query or command:({})' \\\n        '2:file path:_files'\n}}\n\n_minigrep \"$@\"\n",
+        command_names().join(" ")
+    ));
+    script
+}
+
+fn fish() -> String {
+    let mut script = String::new();
+    for (name, help) in COMMANDS {
+        script.push_str(&format!(
+            "complete -c minigrep -n __fish_use_subcommand -a {name} -d '{}'\n",
+            help.replace('\'', "\\'")
+        ));
+    }
+    script.push_str(&format!(
+        "complete -c minigrep -n '__fish_seen_subcommand_from generate' -x -a '{}'\n",
+        TARGETS.join(" ")
+    ));
+    for argument in all_arguments() {
+        let mut line = String::from("complete -c minigrep");
+        let commands = commands_of(argument);
+        if !commands.is_empty() {
+            line.push_str(&format!(
+                " -n '__fish_seen_subcommand_from {}'",
+                commands.join(" ")
+            ));
+        }
+        if let Some(short) = argument.short {
+            line.push_str(&format!(" -s {short}"));
+        }
+        line.push_str(&format!(" -l {}", argument.long));
+        match argument.value {
+            ArgumentValue::None => {}
+            ArgumentValue::FileType => {
+                line.push_str(&format!(" -x -a '{}'", file_type_names().join(" ")))
+            }
+            ArgumentValue::Path => line.push_str(" -r -F"),
+            ArgumentValue::Directory => line.push_str(" -x -a '(__fish_complete_directories)'"),
+            ArgumentValue::Choices(_, choices) => {
+                line.push_str(&format!(" -x -a '{}'", choices.join(" ")))
+            }
+            ArgumentValue::Text(_) => line.push_str(" -x"),
+        }
+        line.push_str(&format!(" -d '{}'\n", argument.help.replace('\'', "\\'")));
+        script.push_str(&line);
+    }
+    script
+}
+
+fn man() -> String {
+    let escape = |text: &str| text.replace('\\', "\\e").replace('-', "\\-");
+    let mut page = format!(
+        ".TH MINIGREP 1 \"\" \"minigrep {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    page.push_str(".SH NAME\nminigrep \\- search for a string in files\n");
+    page.push_str(".SH SYNOPSIS\n.B minigrep\n[\\fIGLOBAL OPTIONS\\fR] [\\fIOPTIONS\\fR] \\fIQUERY\\fR \\fIFILE_PATH\\fR\n.br\n.B minigrep\n[\\fIGLOBAL OPTIONS\\fR] \\fICOMMAND\\fR ...\n");
+    page.push_str(".SH DESCRIPTION\nSearch for \\fIQUERY\\fR in the file at \\fIFILE_PATH\\fR, or in every file of the directory at \\fIFILE_PATH\\fR, and print the matching lines.\n");
+    page.push_str(".SH COMMANDS\n");
+    for (name, help) in COMMANDS {
+        page.push_str(&format!(".TP\n.B {name}\n{}.\n", escape(help)));
+    }
+    let mut sections = vec![
+        ("GLOBAL OPTIONS".to_string(), GLOBAL_ARGUMENTS.as_slice()),
+        ("OPTIONS".to_string(), ARGUMENTS.as_slice()),
+    ];
+    for (name, arguments) in COMMAND_ARGUMENTS {
+        if arguments != ARGUMENTS.as_slice() {
+            sections.push((format!("{} OPTIONS", name.to_uppercase()), arguments));
+        }
+    }
+    for (section, arguments) in sections {
+        page.push_str(&format!(".SH {section}\n"));
+        for argument in arguments {
+            let flags: Vec<String> = flags(argument)
+                .iter()
+                .map(|flag| format!("\\fB{}\\fR", escape(flag)))
+                .collect();
+            page.push_str(&format!(".TP\n{}", flags.join(", ")));
+            if let Some(name) = argument.value.name() {
+                page.push_str(&format!(" \\fI{}\\fR", escape(name)));
+            }
+            page.push_str(&format!("\n{}.\n", escape(argument.help)));
+        }
+    }
+    page.push_str(&format!(
+        ".SH FILE TYPES\nBuilt-in file types: {}.\n",
+        file_type_names().join(", ")
+    ));
+    page.push_str(".SH ENVIRONMENT\n.TP\n.B IGNORE_CASE\nWhen set to \\fBtrue\\fR, the search is case-insensitive.\n");
+    page.push_str(".TP\n.B MINIGREP_HISTORY\nWhen set to \\fBtrue\\fR, the searches are recorded in the history.\n");
+    page.push_str(".SH EXIT STATUS\n");
+    for (code, meaning) in [
+        ("0", "Success."),
+        ("1", "Any other error."),
+        ("64", "Invalid command line arguments."),
+        (
+            "65",
+            "Invalid pattern, or file content that is not valid UTF-8.",
+        ),
+        (
+            "66",
+            "File not found, or a directory where a file was expected.",
+        ),
+        ("74", "Any other input/output error."),
+        ("77", "Permission denied."),
+    ] {
+        page.push_str(&format!(".TP\n.B {code}\n{}\n", escape(meaning)));
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_argument_is_generated() {
+        for generate in [Generate::Bash, Generate::Zsh, Generate::Fish, Generate::Man] {
+            let output = generate.generate();
+            for argument in all_arguments() {
+                let long = match generate {
+                    Generate::Man => format!("\\-\\-{}", argument.long.replace('-', "\\-")),
+                    Generate::Fish => format!("-l {}", argument.long),
+                    _ => format!("--{}", argument.long),
+                };
+                assert!(
+                    output.contains(&long),
+                    "`{long}` missing from {generate:?} output"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_command_is_completed() {
+        for generate in [Generate::Bash, Generate::Zsh, Generate::Fish] {
+            let output = generate.generate();
+            for name in command_names() {
+                assert!(
+                    output.contains(name),
+                    "`{name}` missing from {generate:?} output"
+                );
+            }
+        }
+        // The options of `serve` are only completed after it by bash and fish.
+        assert!(bash().contains(
+            "            serve)\n                COMPREPLY=($(compgen -W \"--root --port --timeout\""
+        ));
+        assert!(fish().contains(
+            "complete -c minigrep -n '__fish_seen_subcommand_from serve' -l root -x -a '(__fish_complete_directories)'"
+        ));
+        assert!(man().contains(".SH SERVE OPTIONS\n.TP\n\\fB\\-\\-root\\fR \\fIDIRECTORY\\fR\n"));
+        // The targets of `generate` are completed after it by bash and fish.
+        assert!(
+            bash().contains("generate)\n            COMPREPLY=($(compgen -W \"bash zsh fish man\"")
+        );
+        assert!(fish().contains("__fish_seen_subcommand_from generate' -x -a 'bash zsh fish man'"));
+    }
+
+    #[test]
+    fn parse_generate() {
+        assert_eq!(Generate::parse("zsh"), Ok(Generate::Zsh));
+        assert!(Generate::parse("powershell").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use colored::*;
+use std::ops::Range;
+
+/// Merges overlapping and adjacent byte ranges, e.g. the matches of `aa` in `aaaa`, into sorted
+/// disjoint ranges.
+pub fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Splits `line` into consecutive segments, `true` for the highlighted ones, without copying it.
+///
+/// The ranges are clipped to the line, so the matches after the cut of a truncated line are
+/// dropped.
+pub fn segments<'a>(line: &'a str, ranges: &[Range<usize>]) -> Vec<(&'a str, bool)> {
+    let ranges = ranges
+        .iter()
+        .map(|range| range.start.min(line.len())..range.end.min(line.len()))
+        .collect();
+    let mut segments = Vec::new();
+    let mut index = 0;
+    for range in merge_ranges(ranges) {
+        if index < range.start {
+            segments.push((&line[index..range.start], false));
+        }
+        segments.push((&line[range.start..range.end], true));
+        index = range.end;
+    }
+    if index < line.len() {
+        segments.push((&line[index..], false));
+    }
+    segments
+}
+
+/// Returns `line` with the text of the ranges in bold red.
+pub fn render(line: &str, ranges: &[Range<usize>]) -> String {
+    segments(line, ranges)
+        .into_iter()
+        .map(|(text, is_highlighted)| {
+            if is_highlighted {
+                text.red().bold().to_string()
+            } else {
+                text.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::search_case_insensitive;
+
+    #[test]
+    fn merge_overlapping_and_adjacent_ranges() {
+        assert_eq!(merge_ranges(vec![0..2, 1..3, 2..4]), vec![0..4]);
+        assert_eq!(merge_ranges(vec![5..7, 0..2, 2..3]), vec![0..3, 5..7]);
+        assert_eq!(merge_ranges(vec![0..6, 1..2, 4..4]), vec![0..6]);
+        assert_eq!(merge_ranges(Vec::new()), Vec::new());
+    }
+
+    #[test]
+    fn segments_of_overlapping_matches() {
+        // The matches of `aa` in `xaaaay`.
+        assert_eq!(
+            segments("xaaaay", &[1..3, 2..4, 3..5]),
+            vec![("x", false), ("aaaa", true), ("y", false)]
+        );
+        assert_eq!(
+            segments("frog bog", &[0..4, 5..8]),
+            vec![("frog", true), (" ", false), ("bog", true)]
+        );
+        assert_eq!(segments("frog", &[]), vec![("frog", false)]);
+    }
+
+    #[test]
+    fn segments_of_multibyte_characters() {
+        assert_eq!(
+            segments("un été là", &[Range { start: 3, end: 8 }]),
+            vec![("un ", false), ("été", true), (" là", false)]
+        );
+        // Ranges past the end of the line are clipped.
+        assert_eq!(
+            segments("un été là", &[3..5, 9..20]),
+            vec![("un ", false), ("é", true), ("té ", false), ("là", true)]
+        );
+    }
+
+    #[test]
+    fn segments_of_case_insensitive_matches() {
+        // `İ` is 2 bytes and its lowercase `i̇` is 3.
+        let lines = ["İx y".to_string()];
+        let ranges: Vec<Range<usize>> = search_case_insensitive("X", &lines)
+            .into_iter()
+            .map(|item| item.start_index..item.end_index + 1)
+            .collect();
+        assert_eq!(
+            segments(&lines[0], &ranges),
+            vec![("İ", false), ("x", true), (" y", false)]
+        );
+    }
+}
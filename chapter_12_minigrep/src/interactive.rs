@@ -0,0 +1,407 @@
+use crate::binary::BinaryFiles;
+use crate::config::Config;
+use crate::error::RunError;
+use crate::reader::read_lines;
+use crate::search::{search, search_case_insensitive};
+use crate::walk;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(Debug, PartialEq)]
+pub struct BrowserMatch {
+    pub file_index: usize,
+    pub line_index: usize,
+    pub ranges: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Continue,
+    Quit,
+    Select(String),
+}
+
+pub struct Browser {
+    files: Vec<(PathBuf, Vec<String>)>,
+    ignore_case: bool,
+    pub query: String,
+    pub matches: Vec<BrowserMatch>,
+    pub selected: usize,
+}
+
+impl Browser {
+    pub fn new(files: Vec<(PathBuf, Vec<String>)>, query: &str, ignore_case: bool) -> Self {
+        let mut browser = Browser {
+            files,
+            ignore_case,
+            query: query.to_string(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        browser.update_matches();
+        browser
+    }
+
+    fn update_matches(&mut self) {
+        self.matches.clear();
+        for (file_index, (_, lines)) in self.files.iter().enumerate() {
+            let matching_items = if self.ignore_case {
+                search_case_insensitive(&self.query, lines)
+            } else {
+                search(&self.query, lines)
+            };
+            for item in matching_items {
+                let range = (item.start_index, item.end_index);
+                match self.matches.last_mut() {
+                    Some(last)
+                        if last.file_index == file_index && last.line_index == item.line_index =>
+                    {
+                        last.ranges.push(range);
+                    }
+                    _ => self.matches.push(BrowserMatch {
+                        file_index,
+                        line_index: item.line_index,
+                        ranges: vec![range],
+                    }),
+                }
+            }
+        }
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    pub fn selection(&self) -> Option<String> {
+        self.matches.get(self.selected).map(|browser_match| {
+            let (file_path, _) = &self.files[browser_match.file_index];
+            format!("{}:{}", file_path.display(), browser_match.line_index + 1)
+        })
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let is_control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if is_control => return Action::Quit,
+            KeyCode::Enter => {
+                return match self.selection() {
+                    Some(selection) => Action::Select(selection),
+                    None => Action::Continue,
+                }
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('p' | 'k') if is_control => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('n' | 'j') if is_control => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-(PAGE_SIZE as isize)),
+            KeyCode::PageDown => self.move_selection(PAGE_SIZE as isize),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(character) if !is_control => {
+                self.query.push(character);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let last_index = self.matches.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + offset).clamp(0, last_index) as usize;
+    }
+
+    /// Rows of the match list: `None` for a file header, `Some(match_index)` for a match.
+    fn list_rows(&self) -> Vec<(Option<usize>, usize)> {
+        let mut rows = Vec::new();
+        for (match_index, browser_match) in self.matches.iter().enumerate() {
+            let is_new_file = match_index == 0
+                || self.matches[match_index - 1].file_index != browser_match.file_index;
+            if is_new_file {
+                rows.push((None, browser_match.file_index));
+            }
+            rows.push((Some(match_index), browser_match.file_index));
+        }
+        rows
+    }
+
+    pub fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let mut output = Vec::new();
+        let header = format!("> {}", self.query);
+        let count = format!(" ({} matching lines)", self.matches.len());
+        output.push(format!("{}{}", header.bold(), count.dark_grey()));
+        let list_height = height.saturating_sub(2) / 2;
+        let preview_height = height.saturating_sub(2 + list_height);
+
+        let rows = self.list_rows();
+        let selected_row = rows
+            .iter()
+            .position(|(match_index, _)| *match_index == Some(self.selected))
+            .unwrap_or(0);
+        let first_row = (selected_row + 1).saturating_sub(list_height);
+        for (match_index, file_index) in rows.iter().skip(first_row).take(list_height) {
+            let (file_path, lines) = &self.files[*file_index];
+            let row = match match_index {
+                None => truncate(&file_path.display().to_string(), width)
+                    .magenta()
+                    .to_string(),
+                Some(match_index) => {
+                    let browser_match = &self.matches[*match_index];
+                    let marker = if *match_index == self.selected {
+                        ">"
+                    } else {
+                        " "
+                    };
+                    let prefix = format!("{marker}{:>5}: ", browser_match.line_index + 1);
+                    let line = highlight(
+                        &lines[browser_match.line_index],
+                        &browser_match.ranges,
+                        width.saturating_sub(prefix.chars().count()),
+                    );
+                    if *match_index == self.selected {
+                        format!("{}{line}", prefix.bold())
+                    } else {
+                        format!("{prefix}{line}")
+                    }
+                }
+            };
+            output.push(row);
+        }
+        output.resize(1 + list_height, String::new());
+
+        let selected_match = self.matches.get(self.selected);
+        let separator = match selected_match {
+            Some(browser_match) => {
+                format!("── {} ", self.files[browser_match.file_index].0.display())
+            }
+            None => String::new(),
+        };
+        let separator_width = width.saturating_sub(separator.chars().count());
+        output.push(
+            truncate(
+                &format!("{separator}{}", "─".repeat(separator_width)),
+                width,
+            )
+            .dark_grey()
+            .to_string(),
+        );
+        if let Some(browser_match) = selected_match {
+            let (_, lines) = &self.files[browser_match.file_index];
+            let first_line = browser_match
+                .line_index
+                .saturating_sub(preview_height / 2)
+                .min(lines.len().saturating_sub(preview_height));
+            for (line_index, line) in lines.iter().enumerate().skip(first_line) {
+                if output.len() >= height {
+                    break;
+                }
+                let prefix = format!("{:>6}: ", line_index + 1);
+                let available_width = width.saturating_sub(prefix.chars().count());
+                if line_index == browser_match.line_index {
+                    let line = highlight(line, &browser_match.ranges, available_width);
+                    output.push(format!("{}{line}", prefix.bold()));
+                } else {
+                    output.push(format!(
+                        "{}{}",
+                        prefix.dark_grey(),
+                        truncate(line, available_width)
+                    ));
+                }
+            }
+        }
+        output.resize(height, String::new());
+        output
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+fn highlight(line: &str, ranges: &[(usize, usize)], width: usize) -> String {
+    let mut output = String::new();
+    for (index, character) in line.char_indices().take(width) {
+        let is_matching = ranges
+            .iter()
+            .any(|(start_index, end_index)| (*start_index..=*end_index).contains(&index));
+        if is_matching {
+            output.push_str(&character.red().bold().to_string());
+        } else {
+            output.push(character);
+        }
+    }
+    output
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped, also after an error.
+///
+/// The interface is drawn on stderr so that the selection printed on stdout can be piped.
+struct Screen {
+    output: io::Stderr,
+}
+
+impl Screen {
+    fn enter() -> Result<Screen, RunError> {
+        terminal::enable_raw_mode()?;
+        let mut screen = Screen {
+            output: io::stderr(),
+        };
+        execute!(screen.output, EnterAlternateScreen, Hide)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.output, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+pub fn run(config: &Config) -> Result<(), RunError> {
+    let mut browser = Browser::new(read_files(config), &config.query, config.ignore_case);
+    let selection = {
+        let mut screen = Screen::enter()?;
+        event_loop(&mut browser, &mut screen.output)?
+    };
+    if let Some(selection) = selection {
+        println!("{selection}");
+    }
+    Ok(())
+}
+
+/// Reads the files like for a search, the unreadable and binary files are skipped.
+fn read_files(config: &Config) -> Vec<(PathBuf, Vec<String>)> {
+    let mut files = Vec::new();
+    for file_path in walk::file_paths(Path::new(&config.file_path), &config.file_types, false) {
+        let Ok(Some(file_lines)) = file_path.and_then(|file_path| read_lines(config, file_path))
+        else {
+            continue;
+        };
+        if file_lines.is_binary && config.binary_files == BinaryFiles::Binary {
+            continue;
+        }
+        files.push((file_lines.file_path, file_lines.lines));
+    }
+    files
+}
+
+fn event_loop(browser: &mut Browser, output: &mut impl Write) -> Result<Option<String>, RunError> {
+    loop {
+        let (width, height) = terminal::size()?;
+        queue!(output, Clear(ClearType::All))?;
+        for (row, line) in browser
+            .render(width as usize, height as usize)
+            .iter()
+            .enumerate()
+        {
+            queue!(output, MoveTo(0, row as u16))?;
+            write!(output, "{line}")?;
+        }
+        output.flush()?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match browser.handle_key(key) {
+                Action::Continue => {}
+                Action::Quit => return Ok(None),
+                Action::Select(selection) => return Ok(Some(selection)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser() -> Browser {
+        let files = vec![
+            (
+                PathBuf::from("poem.txt"),
+                vec![
+                    "I'm nobody! Who are you?".to_string(),
+                    "Are you nobody, too?".to_string(),
+                ],
+            ),
+            (
+                PathBuf::from("song.txt"),
+                vec!["Nobody knows".to_string(), "nobody but you".to_string()],
+            ),
+        ];
+        Browser::new(files, "nobody", false)
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn navigation_and_selection() {
+        let mut browser = browser();
+        assert_eq!(browser.matches.len(), 3);
+        assert_eq!(browser.handle_key(key(KeyCode::Up)), Action::Continue);
+        assert_eq!(browser.selected, 0);
+        browser.handle_key(key(KeyCode::PageDown));
+        assert_eq!(browser.selected, 2);
+        assert_eq!(
+            browser.handle_key(key(KeyCode::Enter)),
+            Action::Select("song.txt:2".to_string())
+        );
+        assert_eq!(browser.handle_key(key(KeyCode::Esc)), Action::Quit);
+    }
+
+    #[test]
+    fn incremental_query() {
+        let mut browser = browser();
+        browser.handle_key(key(KeyCode::PageDown));
+        browser.handle_key(key(KeyCode::Char(',')));
+        assert_eq!(browser.query, "nobody,");
+        assert_eq!(
+            browser.matches,
+            vec![BrowserMatch {
+                file_index: 0,
+                line_index: 1,
+                ranges: vec![(8, 14)],
+            }]
+        );
+        assert_eq!(browser.selected, 0);
+        browser.handle_key(key(KeyCode::Backspace));
+        assert_eq!(browser.matches.len(), 3);
+    }
+
+    #[test]
+    fn skip_unreadable_files() {
+        let arguments = [
+            "minigrep",
+            "--interactive",
+            "search",
+            "tests/fixtures/encodings",
+        ];
+        let config = Config::build(arguments.iter().map(|argument| argument.to_string())).unwrap();
+        let files = read_files(&config);
+        assert_eq!(
+            files,
+            vec![(
+                PathBuf::from("tests/fixtures/encodings/utf8.txt"),
+                vec!["café search".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn render_groups_matches_by_file() {
+        let rows = browser().render(80, 12);
+        assert_eq!(rows.len(), 12);
+        assert!(rows[1].contains("poem.txt"));
+        assert!(rows[4].contains("song.txt"));
+    }
+}
@@ -0,0 +1,445 @@
+use crate::search::MatchingItem;
+
+#[derive(Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Spanned>),
+    Object(Vec<(String, Spanned)>),
+}
+
+/// A JSON value with the byte range (`start..end`) of its raw text in the parsed line.
+#[derive(Debug, PartialEq)]
+pub struct Spanned {
+    pub value: JsonValue,
+    pub start_index: usize,
+    pub end_index: usize,
+}
+
+impl Spanned {
+    /// Byte range of the searchable text of the value, without the quotes of strings.
+    pub fn content_range(&self) -> (usize, usize) {
+        match self.value {
+            JsonValue::String(_) => (self.start_index + 1, self.end_index - 1),
+            _ => (self.start_index, self.end_index),
+        }
+    }
+
+    pub fn get(&self, path: &[PathSegment]) -> Option<&Spanned> {
+        let Some((segment, rest)) = path.split_first() else {
+            return Some(self);
+        };
+        let child = match (segment, &self.value) {
+            (PathSegment::Key(key), JsonValue::Object(entries)) => entries
+                .iter()
+                .rev()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            (PathSegment::Index(index), JsonValue::Array(values)) => values.get(*index),
+            _ => None,
+        };
+        child.and_then(|child| child.get(rest))
+    }
+
+    /// Text used to compare the value in predicates: the decoded string, or the raw JSON.
+    fn text<'a>(&'a self, line: &'a str) -> &'a str {
+        match &self.value {
+            JsonValue::String(string) => string,
+            _ => &line[self.start_index..self.end_index],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Predicate {
+    pub path: Vec<PathSegment>,
+    pub value: String,
+    pub is_negated: bool,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct JsonFilter {
+    /// Path of the value to search in, the whole line is searched when `None`.
+    pub path: Option<Vec<PathSegment>>,
+    pub predicates: Vec<Predicate>,
+}
+
+/// Parses a key path like `.error.message` or `.items[0].name` (the leading `.` is optional).
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let error = || format!("Error: Invalid JSON path `{path}`.");
+    let mut segments = Vec::new();
+    for part in path.strip_prefix('.').unwrap_or(path).split('.') {
+        let (key, mut indexes) = match part.find('[') {
+            Some(index) => part.split_at(index),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+        while !indexes.is_empty() {
+            let end_index = indexes.find(']').ok_or_else(error)?;
+            let index = indexes[1..end_index].parse().map_err(|_| error())?;
+            segments.push(PathSegment::Index(index));
+            indexes = &indexes[end_index + 1..];
+            if !indexes.is_empty() && !indexes.starts_with('[') {
+                return Err(error());
+            }
+        }
+        if key.is_empty() && part.is_empty() && path != "." {
+            return Err(error());
+        }
+    }
+    Ok(segments)
+}
+
+/// Parses a `--where` predicate like `level=error` or `.http.status!=200`.
+pub fn parse_predicate(predicate: &str) -> Result<Predicate, String> {
+    let (path, value, is_negated) = match predicate.split_once("!=") {
+        Some((path, value)) => (path, value, true),
+        None => match predicate.split_once('=') {
+            Some((path, value)) => (path, value, false),
+            None => {
+                return Err(format!(
+                "Error: Invalid predicate `{predicate}`, expected `path=value` or `path!=value`."
+            ))
+            }
+        },
+    };
+    Ok(Predicate {
+        path: parse_path(path)?,
+        value: value.to_string(),
+        is_negated,
+    })
+}
+
+impl JsonFilter {
+    /// Keeps the matches of the lines that are valid JSON, satisfy every predicate and whose
+    /// match is inside the value at `path`.
+    pub fn filter_matching_items(
+        &self,
+        lines: &[String],
+        matching_items: Vec<MatchingItem>,
+    ) -> Vec<MatchingItem> {
+        let mut parsed_line: Option<(usize, Option<(usize, usize)>)> = None;
+        matching_items
+            .into_iter()
+            .filter(|item| {
+                let range = match parsed_line {
+                    Some((line_index, range)) if line_index == item.line_index => range,
+                    _ => {
+                        let range = self.searchable_range(&lines[item.line_index]);
+                        parsed_line = Some((item.line_index, range));
+                        range
+                    }
+                };
+                range.is_some_and(|(start_index, end_index)| {
+                    item.start_index >= start_index && item.end_index < end_index
+                })
+            })
+            .collect()
+    }
+
+    fn searchable_range(&self, line: &str) -> Option<(usize, usize)> {
+        let root = parse(line)?;
+        let is_matching_predicates = self.predicates.iter().all(|predicate| {
+            let is_equal = root
+                .get(&predicate.path)
+                .is_some_and(|value| value.text(line) == predicate.value);
+            is_equal != predicate.is_negated
+        });
+        if !is_matching_predicates {
+            return None;
+        }
+        match &self.path {
+            Some(path) => root.get(path).map(Spanned::content_range),
+            None => Some((0, line.len())),
+        }
+    }
+}
+
+/// Nesting depth of the arrays and objects past which a text is not parsed, so that deeply
+/// nested input cannot overflow the stack of the recursive parser.
+const MAX_DEPTH: usize = 128;
+
+pub fn parse(text: &str) -> Option<Spanned> {
+    let mut parser = Parser {
+        text,
+        index: 0,
+        depth: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.index == text.len() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// Writes `text` as a JSON string, with its quotes.
+pub fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            character if character.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    index: usize,
+    /// Number of arrays and objects being parsed.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.index).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.index += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.index += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<Spanned> {
+        self.skip_whitespace();
+        let start_index = self.index;
+        let value = match self.peek()? {
+            b'{' | b'[' if self.depth == MAX_DEPTH => return None,
+            b'{' => self.nested(Self::parse_object)?,
+            b'[' => self.nested(Self::parse_array)?,
+            b'"' => JsonValue::String(self.parse_string()?),
+            b't' => self.parse_literal("true", JsonValue::Bool(true))?,
+            b'f' => self.parse_literal("false", JsonValue::Bool(false))?,
+            b'n' => self.parse_literal("null", JsonValue::Null)?,
+            _ => self.parse_number()?,
+        };
+        Some(Spanned {
+            value,
+            start_index,
+            end_index: self.index,
+        })
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Option<JsonValue>) -> Option<JsonValue> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Option<JsonValue> {
+        if self.text[self.index..].starts_with(literal) {
+            self.index += literal.len();
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<JsonValue> {
+        let start_index = self.index;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.index += 1;
+        }
+        let number = &self.text[start_index..self.index];
+        number
+            .parse::<f64>()
+            .ok()
+            .map(|_| JsonValue::Number(number.to_string()))
+    }
+
+    fn parse_string(&mut self) -> Option<String> {
+        self.index += 1;
+        let mut string = String::new();
+        let mut characters = self.text[self.index..].char_indices();
+        while let Some((offset, character)) = characters.next() {
+            match character {
+                '"' => {
+                    self.index += offset + 1;
+                    return Some(string);
+                }
+                '\\' => {
+                    let (_, escaped) = characters.next()?;
+                    match escaped {
+                        '"' | '\\' | '/' => string.push(escaped),
+                        'b' => string.push('\u{8}'),
+                        'f' => string.push('\u{c}'),
+                        'n' => string.push('\n'),
+                        'r' => string.push('\r'),
+                        't' => string.push('\t'),
+                        'u' => {
+                            let mut code_point = 0;
+                            for _ in 0..4 {
+                                let (_, digit) = characters.next()?;
+                                code_point = code_point * 16 + digit.to_digit(16)?;
+                            }
+                            // Lone surrogates are replaced, surrogate pairs are not decoded.
+                            string.push(char::from_u32(code_point).unwrap_or('\u{fffd}'));
+                        }
+                        _ => return None,
+                    }
+                }
+                _ => string.push(character),
+            }
+        }
+        None
+    }
+
+    fn parse_array(&mut self) -> Option<JsonValue> {
+        self.index += 1;
+        let mut values = Vec::new();
+        if self.expect(b']').is_some() {
+            return Some(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            if self.expect(b',').is_none() {
+                self.expect(b']')?;
+                return Some(JsonValue::Array(values));
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Option<JsonValue> {
+        self.index += 1;
+        let mut entries = Vec::new();
+        if self.expect(b'}').is_some() {
+            return Some(JsonValue::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return None;
+            }
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            entries.push((key, self.parse_value()?));
+            if self.expect(b',').is_none() {
+                self.expect(b'}')?;
+                return Some(JsonValue::Object(entries));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_round_trip() {
+        let text = "say \"hi\"\t\\ \u{0} été";
+        let quoted = quote(text);
+        assert_eq!(quoted, r#""say \"hi\"\t\\ \u0000 été""#);
+        assert_eq!(
+            parse(&quoted).unwrap().value,
+            JsonValue::String(text.to_string())
+        );
+    }
+
+    #[test]
+    fn parse_and_get_path() {
+        let line =
+            r#"{"level": "error", "error": {"message": "disk \"full\""}, "tags": [1, true]}"#;
+        let root = parse(line).unwrap();
+        let message = root.get(&parse_path(".error.message").unwrap()).unwrap();
+        assert_eq!(
+            message.value,
+            JsonValue::String(r#"disk "full""#.to_string())
+        );
+        let (start_index, end_index) = message.content_range();
+        assert_eq!(&line[start_index..end_index], r#"disk \"full\""#);
+        let tag = root.get(&parse_path(".tags[1]").unwrap()).unwrap();
+        assert_eq!(tag.value, JsonValue::Bool(true));
+        assert!(parse(r#"{"level": "error""#).is_none());
+        assert!(parse("not json").is_none());
+    }
+
+    #[test]
+    fn parse_nesting_up_to_max_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_some());
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_none());
+        assert!(parse(&"[".repeat(200_000)).is_none());
+    }
+
+    #[test]
+    fn parse_paths_and_predicates() {
+        assert_eq!(
+            parse_path("items[0].name").unwrap(),
+            vec![
+                PathSegment::Key("items".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Key("name".to_string()),
+            ]
+        );
+        assert!(parse_path(".items[a]").is_err());
+        assert!(parse_path("..a").is_err());
+        assert_eq!(
+            parse_predicate("level!=info").unwrap(),
+            Predicate {
+                path: vec![PathSegment::Key("level".to_string())],
+                value: "info".to_string(),
+                is_negated: true,
+            }
+        );
+        assert!(parse_predicate("level").is_err());
+    }
+
+    #[test]
+    fn filter_by_path_and_predicates() {
+        let filter = JsonFilter {
+            path: Some(parse_path(".message").unwrap()),
+            predicates: vec![parse_predicate("level=error").unwrap()],
+        };
+        let lines = [
+            r#"{"level":"error","message":"disk full","host":"disk-1"}"#.to_string(),
+            r#"{"level":"info","message":"disk ok"}"#.to_string(),
+        ];
+        let matching_items = crate::search::search("disk", &lines);
+        assert_eq!(
+            filter.filter_matching_items(&lines, matching_items),
+            vec![MatchingItem {
+                start_index: 28,
+                end_index: 31,
+                line_index: 0,
+            }]
+        );
+    }
+}
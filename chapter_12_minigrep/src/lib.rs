@@ -1,43 +1,455 @@
+use binary::BinaryFiles;
 use colored::*;
 use config::Config;
 use error::RunError;
-use search::{search, search_case_insensitive};
+use format::FormatValues;
+use reader::{read_file, read_lines, read_lines_from_bytes, FileLines};
+use search::{search, search_case_insensitive, MatchingItem};
+use stats::Stats;
 use std::fs;
+use std::io::{self, BufRead, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use unique::UniqueLines;
 
+pub mod archive;
+pub mod arguments;
+pub mod binary;
+pub mod command;
 pub mod config;
+pub mod diff;
 pub mod error;
+pub mod fields;
+pub mod file_types;
+pub mod format;
+pub mod generate;
+pub mod highlight;
+pub mod interactive;
+pub mod json;
+pub mod limits;
+pub mod lsp;
+pub mod preprocess;
+pub mod query;
+pub mod reader;
+pub mod scope;
 pub mod search;
+pub mod serve;
+pub mod stats;
+pub mod store;
+pub mod terminator;
+pub mod unique;
+pub mod walk;
 
 pub fn run(config: &Config) -> Result<(), RunError> {
-    let file_content = fs::read_to_string(&config.file_path)?;
-    let mut lines: Vec<String> = file_content.lines().map(|line| line.to_string()).collect();
-    let matching_items = if config.ignore_case {
-        search_case_insensitive(&config.query, &lines)
+    let start_time = Instant::now();
+    let mut stats = Stats::default();
+    let mut unique_lines = UniqueLines::default();
+    if config.diff {
+        run_diff(config, &mut stats, &mut unique_lines)?;
+    } else if config.file_path == "-" {
+        run_stdin(config, &mut stats, &mut unique_lines)?;
     } else {
-        search(&config.query, &lines)
-    };
-    let mut lines_indexes_differences_colored: Vec<usize> = vec![0; lines.len()];
-    let mut matching_line_indexes: Vec<usize> = Vec::new();
-    for item in matching_items {
-        let has_already_matched = matching_line_indexes.contains(&item.line_index);
-        if !has_already_matched {
-            matching_line_indexes.push(item.line_index);
+        run_files(config, &mut stats, &mut unique_lines)?;
+    }
+    if config.stats {
+        stats.print(start_time.elapsed());
+    }
+    Ok(())
+}
+
+fn run_files(
+    config: &Config,
+    stats: &mut Stats,
+    unique_lines: &mut UniqueLines,
+) -> Result<(), RunError> {
+    let path = Path::new(&config.file_path);
+    let is_directory = path.is_dir();
+    let mut file_paths = walk::file_paths(path, &config.file_types, config.search_archives);
+    if let Some(sort) = config.sort {
+        walk::sort_file_paths(&mut file_paths, sort);
+    }
+    for file_path in file_paths {
+        // The members of an archive are searched and printed by `run_archive`.
+        let file_lines = file_path.and_then(|file_path| {
+            if config.search_archives && archive::is_archive(&file_path) {
+                run_archive(config, &file_path, stats, unique_lines).map(|_| None)
+            } else {
+                read_lines(config, file_path)
+            }
+        });
+        let file_lines = match file_lines {
+            Ok(Some(file_lines)) => file_lines,
+            Ok(None) => continue,
+            Err(_) if config.no_messages => continue,
+            Err(error) => return Err(error),
+        };
+        let prefix = if is_directory {
+            path_prefix(config, &file_lines.file_path.display().to_string())
+        } else {
+            String::new()
+        };
+        let bytes_count = fs::metadata(&file_lines.file_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        search_and_print(
+            config,
+            file_lines,
+            |_, _| prefix.clone(),
+            stats,
+            unique_lines,
+            bytes_count,
+        );
+    }
+    Ok(())
+}
+
+/// Searches each member of the archive whose type matches like a file named `archive!/member`,
+/// each line is prefixed by this name and its line number.
+fn run_archive(
+    config: &Config,
+    archive_path: &Path,
+    stats: &mut Stats,
+    unique_lines: &mut UniqueLines,
+) -> Result<(), RunError> {
+    for member in archive::read_members(archive_path)? {
+        let file_name = member.path.rsplit('/').next().unwrap_or(&member.path);
+        if !config.file_types.is_match(file_name) {
+            continue;
         }
-        let line_index_difference_colored = lines_indexes_differences_colored[item.line_index];
-        let start_index = item.start_index + line_index_difference_colored;
-        let end_index = item.end_index + line_index_difference_colored;
-        let matching_text = lines[item.line_index]
-            .get(start_index..=end_index)
-            .expect("Failed to get `matching_text`.");
-        let colored_text = matching_text.red().bold().to_string();
-        lines_indexes_differences_colored[item.line_index] +=
-            colored_text.len() - matching_text.len();
-        lines[item.line_index].replace_range(start_index..=end_index, &colored_text);
-    }
-    for (index, line) in lines.iter().enumerate() {
-        if matching_line_indexes.contains(&index) {
-            println!("{line}");
+        let member_path = PathBuf::from(format!("{}!/{}", archive_path.display(), member.path));
+        let bytes_count = member.content.len() as u64;
+        let file_lines = match read_lines_from_bytes(config, member_path, member.content) {
+            Ok(Some(file_lines)) => file_lines,
+            Ok(None) => continue,
+            Err(_) if config.no_messages => continue,
+            Err(error) => return Err(error),
+        };
+        let prefix = path_prefix(config, &file_lines.file_path.display().to_string());
+        search_and_print(
+            config,
+            file_lines,
+            |_, file_line_index| format!("{prefix}{}:", (file_line_index + 1).to_string().green()),
+            stats,
+            unique_lines,
+            bytes_count,
+        );
+    }
+    Ok(())
+}
+
+/// Searches stdin. With `--passthru`, each line is searched and printed as soon as it is read so
+/// that minigrep can highlight a stream like `tail -f`, unless `--field` or `--stats` need the
+/// whole input.
+fn run_stdin(
+    config: &Config,
+    stats: &mut Stats,
+    unique_lines: &mut UniqueLines,
+) -> Result<(), RunError> {
+    let file_path = PathBuf::from("(standard input)");
+    if !config.passthru || config.fields.is_some() || config.stats {
+        let mut input = Vec::new();
+        io::stdin().read_to_end(&mut input)?;
+        let bytes_count = input.len() as u64;
+        if let Some(file_lines) = read_lines_from_bytes(config, file_path, input)? {
+            search_and_print(
+                config,
+                file_lines,
+                |_, _| String::new(),
+                stats,
+                unique_lines,
+                bytes_count,
+            );
         }
+        return Ok(());
+    }
+    let separator = config.line_terminator.separator();
+    for (line_index, line) in io::stdin().lock().split(separator).enumerate() {
+        let line = line
+            .and_then(|line| {
+                String::from_utf8(line)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            })
+            .map_err(|error| RunError::from_io_error(error, &file_path))?;
+        let bytes_count = line.len() as u64 + 1;
+        let (line, line_terminator) = config.line_terminator.trim(&line);
+        let file_lines = FileLines {
+            file_path: file_path.clone(),
+            line_indexes: vec![line_index],
+            lines: vec![line.to_string()],
+            is_binary: false,
+            line_terminators: vec![line_terminator],
+        };
+        search_and_print(
+            config,
+            file_lines,
+            |_, _| String::new(),
+            stats,
+            unique_lines,
+            bytes_count,
+        );
+    }
+    Ok(())
+}
+
+/// Searches only the added and removed lines of the unified diff at `config.file_path` (`-` for
+/// stdin), each line is prefixed by its path, post-image line number and `+`/`-` marker.
+fn run_diff(
+    config: &Config,
+    stats: &mut Stats,
+    unique_lines: &mut UniqueLines,
+) -> Result<(), RunError> {
+    let diff = if config.file_path == "-" {
+        let mut diff = String::new();
+        io::stdin().read_to_string(&mut diff)?;
+        diff
+    } else {
+        let (_, diff) = read_file(PathBuf::from(&config.file_path))?;
+        diff
+    };
+    for diff_file in diff::parse(&diff) {
+        let prefix = path_prefix(
+            config,
+            &diff_file.file_lines.file_path.display().to_string(),
+        );
+        let bytes_count = diff_file
+            .file_lines
+            .lines
+            .iter()
+            .map(|line| line.len() as u64 + 1)
+            .sum();
+        search_and_print(
+            config,
+            diff_file.file_lines,
+            |line_index, file_line_index| {
+                format!(
+                    "{prefix}{}:{}",
+                    (file_line_index + 1).to_string().green(),
+                    diff_file.kinds[line_index].marker()
+                )
+            },
+            stats,
+            unique_lines,
+            bytes_count,
+        );
     }
     Ok(())
 }
+
+/// `line_prefix` is called with the index of a line in `file_lines.lines` and its index in the
+/// file.
+fn search_and_print(
+    config: &Config,
+    file_lines: FileLines,
+    line_prefix: impl Fn(usize, usize) -> String,
+    stats: &mut Stats,
+    unique_lines: &mut UniqueLines,
+    bytes_count: u64,
+) {
+    let (file_lines, mut matching_line_indexes, mut matching_items) =
+        search_file_lines(config, file_lines);
+    let line_prefix =
+        |line_index: usize| line_prefix(line_index, file_lines.line_indexes[line_index]);
+    if config.stats {
+        let matched_lines: Vec<&str> = matching_line_indexes
+            .iter()
+            .map(|line_index| file_lines.lines[*line_index].as_str())
+            .collect();
+        stats.add_file(
+            &file_lines.file_path.display().to_string(),
+            bytes_count,
+            &matched_lines,
+            non_overlapping_items(&matching_items).len(),
+        );
+    }
+    if config.unique {
+        matching_line_indexes
+            .retain(|line_index| unique_lines.insert(&file_lines.lines[*line_index]));
+        matching_items.retain(|item| {
+            matching_line_indexes
+                .binary_search(&item.line_index)
+                .is_ok()
+        });
+    }
+    if file_lines.is_binary && config.binary_files == BinaryFiles::Binary {
+        if !matching_line_indexes.is_empty() {
+            println!("Binary file {} matches", file_lines.file_path.display());
+        }
+    } else if let Some(template) = &config.format {
+        print_formatted_matches(template, &file_lines, &matching_items);
+    } else if config.only_matching {
+        print_only_matching(&file_lines, &matching_items, line_prefix);
+    } else {
+        let printed_line_indexes: Vec<usize> = if config.passthru {
+            (0..file_lines.lines.len()).collect()
+        } else {
+            if config.fields.is_some() && !matching_line_indexes.is_empty() {
+                print!(
+                    "{}{}{}",
+                    line_prefix(0),
+                    file_lines.lines[0].bold(),
+                    file_lines.line_terminators[0]
+                );
+            }
+            matching_line_indexes
+        };
+        print_matching_lines(
+            &file_lines.lines,
+            &printed_line_indexes,
+            &matching_items,
+            line_prefix,
+            config.max_columns,
+            &file_lines.line_terminators,
+        );
+    }
+}
+
+/// Searches the lines of a file, once joined into records with `--field`, restricted to `--lines`
+/// and masked outside of `--scope`. Returns these lines, the indexes of the matching ones and the matches.
+fn search_file_lines(
+    config: &Config,
+    mut file_lines: FileLines,
+) -> (FileLines, Vec<usize>, Vec<MatchingItem>) {
+    if config.fields.is_some() {
+        file_lines = fields::records(file_lines);
+    }
+    // With `--scope`, the masked lines are searched instead, their matches have the same ranges.
+    let mut masked_lines = config
+        .scope
+        .map(|scope| scope::mask(&file_lines.lines, scope));
+    if let Some(line_range) = config.line_range {
+        // The header of delimited files is kept to print it and to skip it when searching.
+        let is_kept = |line_index: usize| {
+            line_range.contains(line_index) || (config.fields.is_some() && line_index == 0)
+        };
+        if let Some(masked_lines) = &mut masked_lines {
+            let mut line_indexes = file_lines.line_indexes.iter();
+            masked_lines.retain(|_| line_indexes.next().is_some_and(|index| is_kept(*index)));
+        }
+        let mut line_indexes = file_lines.line_indexes.iter();
+        file_lines
+            .line_terminators
+            .retain(|_| line_indexes.next().is_some_and(|index| is_kept(*index)));
+        let (line_indexes, lines) = file_lines
+            .line_indexes
+            .into_iter()
+            .zip(file_lines.lines)
+            .filter(|(line_index, _)| is_kept(*line_index))
+            .unzip();
+        file_lines.line_indexes = line_indexes;
+        file_lines.lines = lines;
+    }
+    let searched_lines = masked_lines.as_ref().unwrap_or(&file_lines.lines);
+    let (matching_line_indexes, matching_items) = match &config.boolean_query {
+        Some(query) => query.search(searched_lines, config.ignore_case),
+        None => {
+            let mut matching_items = if config.ignore_case {
+                search_case_insensitive(&config.query, searched_lines)
+            } else {
+                search(&config.query, searched_lines)
+            };
+            if let Some(fields) = &config.fields {
+                matching_items = fields.filter_matching_items(&file_lines.lines, matching_items);
+            }
+            if let Some(json) = &config.json {
+                matching_items = json.filter_matching_items(&file_lines.lines, matching_items);
+            }
+            let mut matching_line_indexes: Vec<usize> =
+                matching_items.iter().map(|item| item.line_index).collect();
+            matching_line_indexes.dedup();
+            (matching_line_indexes, matching_items)
+        }
+    };
+    (file_lines, matching_line_indexes, matching_items)
+}
+
+/// The path of a file followed by `:`, or NUL with `-0`.
+fn path_prefix(config: &Config, path: &str) -> String {
+    let separator = if config.null { "\0" } else { ":" };
+    format!("{}{separator}", path.magenta())
+}
+
+/// Skips the matches overlapping a previous match of the same line, e.g. `aa` in `aaa`.
+fn non_overlapping_items(matching_items: &[MatchingItem]) -> Vec<&MatchingItem> {
+    let mut items: Vec<&MatchingItem> = Vec::new();
+    for item in matching_items {
+        let is_overlapping = items.last().is_some_and(|last| {
+            last.line_index == item.line_index && last.end_index >= item.start_index
+        });
+        if !is_overlapping {
+            items.push(item);
+        }
+    }
+    items
+}
+
+fn print_formatted_matches(
+    template: &str,
+    file_lines: &FileLines,
+    matching_items: &[MatchingItem],
+) {
+    let path = file_lines.file_path.display().to_string();
+    for item in non_overlapping_items(matching_items) {
+        let text = &file_lines.lines[item.line_index];
+        let values = FormatValues {
+            path: &path,
+            line: file_lines.line_indexes[item.line_index] + 1,
+            col: item.start_index + 1,
+            matching_text: &text[item.start_index..=item.end_index],
+            text,
+        };
+        println!("{}", format::render(template, &values));
+    }
+}
+
+fn print_only_matching(
+    file_lines: &FileLines,
+    matching_items: &[MatchingItem],
+    line_prefix: impl Fn(usize) -> String,
+) {
+    for item in non_overlapping_items(matching_items) {
+        let matching_text = &file_lines.lines[item.line_index][item.start_index..=item.end_index];
+        print!(
+            "{}{}{}",
+            line_prefix(item.line_index),
+            matching_text.red().bold(),
+            file_lines.line_terminators[item.line_index]
+        );
+    }
+}
+
+/// Prints the lines of `printed_line_indexes` with their matches highlighted, each followed by
+/// its terminator of `line_terminators`. Lines longer than `max_columns` bytes are truncated, the matches after the
+/// cut are dropped.
+fn print_matching_lines(
+    lines: &[String],
+    printed_line_indexes: &[usize],
+    matching_items: &[MatchingItem],
+    line_prefix: impl Fn(usize) -> String,
+    max_columns: Option<usize>,
+    line_terminators: &[&str],
+) {
+    let mut matching_ranges: Vec<Vec<Range<usize>>> = vec![Vec::new(); lines.len()];
+    for item in matching_items {
+        matching_ranges[item.line_index].push(item.start_index..item.end_index + 1);
+    }
+    for index in printed_line_indexes {
+        let line = &lines[*index];
+        let truncation_index = match max_columns {
+            Some(max_columns) => limits::truncation_index(line, max_columns),
+            None => line.len(),
+        };
+        let marker = match line.len() - truncation_index {
+            0 => String::new(),
+            truncated_bytes_count => limits::truncation_marker(truncated_bytes_count)
+                .dimmed()
+                .to_string(),
+        };
+        print!(
+            "{}{}{marker}{}",
+            line_prefix(*index),
+            highlight::render(&line[..truncation_index], &matching_ranges[*index]),
+            line_terminators[*index]
+        );
+    }
+}
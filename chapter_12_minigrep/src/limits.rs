@@ -0,0 +1,109 @@
+/// Lines to search, numbered from 1 and inclusive, e.g. `--lines 100:200`, `100:` or `:200`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl LineRange {
+    pub fn parse(value: &str) -> Result<LineRange, String> {
+        let error =
+            || format!("Error: Invalid line range `{value}`, expected START:END like 100:200.");
+        let (start, end) = value.split_once(':').ok_or_else(error)?;
+        let parse_line = |line: &str| match line.parse::<usize>() {
+            Ok(line) if line > 0 => Ok(line),
+            _ => Err(error()),
+        };
+        let start = if start.is_empty() {
+            1
+        } else {
+            parse_line(start)?
+        };
+        let end = if end.is_empty() {
+            None
+        } else {
+            Some(parse_line(end)?)
+        };
+        if end.is_some_and(|end| end < start) {
+            return Err(error());
+        }
+        Ok(LineRange { start, end })
+    }
+
+    /// `line_index` is 0-based.
+    pub fn contains(&self, line_index: usize) -> bool {
+        line_index + 1 >= self.start && self.end.is_none_or(|end| line_index < end)
+    }
+}
+
+pub fn parse_max_columns(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(max_columns) if max_columns > 0 => Ok(max_columns),
+        _ => Err(format!(
+            "Error: Invalid maximum number of columns `{value}`, expected a positive number."
+        )),
+    }
+}
+
+/// Byte index at which a line longer than `max_columns` bytes is cut, on a character boundary.
+pub fn truncation_index(line: &str, max_columns: usize) -> usize {
+    if line.len() <= max_columns {
+        return line.len();
+    }
+    (0..=max_columns)
+        .rev()
+        .find(|index| line.is_char_boundary(*index))
+        .unwrap_or(0)
+}
+
+pub fn truncation_marker(truncated_bytes_count: usize) -> String {
+    format!(" [... {truncated_bytes_count} more bytes]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_line_range() {
+        assert_eq!(
+            LineRange::parse("100:200"),
+            Ok(LineRange {
+                start: 100,
+                end: Some(200)
+            })
+        );
+        assert_eq!(
+            LineRange::parse(":3"),
+            Ok(LineRange {
+                start: 1,
+                end: Some(3)
+            })
+        );
+        assert_eq!(
+            LineRange::parse("5:"),
+            Ok(LineRange {
+                start: 5,
+                end: None
+            })
+        );
+        assert!(LineRange::parse("200:100").is_err());
+        assert!(LineRange::parse("0:10").is_err());
+        assert!(LineRange::parse("10").is_err());
+
+        let line_range = LineRange::parse("2:3").unwrap();
+        let line_indexes: Vec<usize> = (0..5)
+            .filter(|line_index| line_range.contains(*line_index))
+            .collect();
+        assert_eq!(line_indexes, vec![1, 2]);
+    }
+
+    #[test]
+    fn truncate_on_character_boundary() {
+        assert_eq!(truncation_index("frog", 10), 4);
+        assert_eq!(truncation_index("frog", 2), 2);
+        assert_eq!(truncation_index("été", 2), 2);
+        assert_eq!(truncation_index("été", 1), 0);
+        assert_eq!(truncation_marker(12), " [... 12 more bytes]");
+    }
+}
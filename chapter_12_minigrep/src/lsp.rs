@@ -0,0 +1,476 @@
+use crate::binary::BinaryFiles;
+use crate::config::Config;
+use crate::error::RunError;
+use crate::json::{self, JsonValue, PathSegment, Spanned};
+use crate::reader::{read_lines, FileLines};
+use crate::search::MatchingItem;
+use crate::{non_overlapping_items, search_file_lines, walk};
+use std::collections::HashMap;
+use std::env;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const REQUEST_CANCELLED: i32 = -32800;
+
+/// Largest accepted message content, in bytes.
+const MAX_CONTENT_LENGTH: usize = 16 * 1024 * 1024;
+
+/// Stdout, shared with the threads of the searches so that their messages are not interleaved.
+type Output = Arc<Mutex<dyn Write + Send>>;
+
+/// The cancellation flag of each running search, by raw JSON request id.
+type Searches = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
+
+#[derive(Debug, PartialEq)]
+enum Flow {
+    Continue,
+    Exit,
+}
+
+struct Server {
+    root: PathBuf,
+    searches: Searches,
+    is_shut_down: bool,
+    output: Output,
+}
+
+/// Speaks JSON-RPC on stdin and stdout until the `exit` notification. Like other language
+/// servers, the exit code is 1 if the client did not send `shutdown` first.
+pub fn run() -> Result<(), RunError> {
+    let mut server = Server {
+        root: env::current_dir()?,
+        searches: Arc::new(Mutex::new(HashMap::new())),
+        is_shut_down: false,
+        output: Arc::new(Mutex::new(io::stdout())),
+    };
+    let mut input = BufReader::new(io::stdin().lock());
+    while let Some(message) = read_message(&mut input)? {
+        if server.handle(&message) == Flow::Exit {
+            break;
+        }
+    }
+    if server.is_shut_down {
+        Ok(())
+    } else {
+        Err(RunError::Other(
+            "the client exited without a shutdown request".into(),
+        ))
+    }
+}
+
+/// Reads the content of a message framed by a `Content-Length` header, `None` at the end of input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() && content_length.is_some() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let content_length = content_length.unwrap_or(0);
+    if content_length > MAX_CONTENT_LENGTH {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("message of {content_length} bytes, more than {MAX_CONTENT_LENGTH}"),
+        ));
+    }
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+}
+
+fn send(output: &Output, message: &str) {
+    let mut output = output.lock().unwrap_or_else(|error| error.into_inner());
+    // The client is gone if stdout is closed, the next read of stdin will end the loop.
+    let _ = write!(output, "Content-Length: {}\r\n\r\n{message}", message.len())
+        .and_then(|_| output.flush());
+}
+
+fn response(id: &str, result: &str) -> String {
+    format!("{{\"jsonrpc\":\"2.0\",\"id\":{id},\"result\":{result}}}")
+}
+
+fn error_response(id: &str, code: i32, message: &str) -> String {
+    format!(
+        "{{\"jsonrpc\":\"2.0\",\"id\":{id},\"error\":{{\"code\":{code},\"message\":{}}}}}",
+        json::quote(message)
+    )
+}
+
+fn field<'a>(value: &'a Spanned, key: &str) -> Option<&'a Spanned> {
+    value.get(&[PathSegment::Key(key.to_string())])
+}
+
+impl Server {
+    fn handle(&mut self, text: &str) -> Flow {
+        let Some(message) = json::parse(text) else {
+            send(
+                &self.output,
+                &error_response("null", PARSE_ERROR, "invalid JSON"),
+            );
+            return Flow::Continue;
+        };
+        // The id is echoed as is, it can be a number or a string.
+        let id = field(&message, "id").map(|id| &text[id.start_index..id.end_index]);
+        let params = field(&message, "params");
+        let method = match field(&message, "method").map(|method| &method.value) {
+            Some(JsonValue::String(method)) => method.as_str(),
+            _ => {
+                let id = id.unwrap_or("null");
+                send(
+                    &self.output,
+                    &error_response(id, INVALID_REQUEST, "missing method"),
+                );
+                return Flow::Continue;
+            }
+        };
+        match (method, id) {
+            ("initialize", Some(id)) => {
+                if let Some(root) = params.and_then(workspace_root) {
+                    self.root = root.canonicalize().unwrap_or(root);
+                }
+                let result = format!(
+                    "{{\"capabilities\":{{\"experimental\":{{\"textSearchProvider\":true}}}},\"serverInfo\":{{\"name\":\"minigrep\",\"version\":\"{}\"}}}}",
+                    env!("CARGO_PKG_VERSION")
+                );
+                send(&self.output, &response(id, &result));
+            }
+            ("shutdown", Some(id)) => {
+                self.is_shut_down = true;
+                send(&self.output, &response(id, "null"));
+            }
+            ("exit", _) => return Flow::Exit,
+            ("workspace/textSearch", Some(id)) => self.start_search(id, params),
+            ("$/cancelRequest", None) => {
+                let cancelled_id = params
+                    .and_then(|params| field(params, "id"))
+                    .map(|id| &text[id.start_index..id.end_index]);
+                let searches = self
+                    .searches
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner());
+                if let Some(is_cancelled) = cancelled_id.and_then(|id| searches.get(id)) {
+                    is_cancelled.store(true, Ordering::Relaxed);
+                }
+            }
+            (_, Some(id)) => {
+                let message = format!("unknown method `{method}`");
+                send(
+                    &self.output,
+                    &error_response(id, METHOD_NOT_FOUND, &message),
+                );
+            }
+            // Other notifications, like `initialized`, need no answer.
+            (_, None) => {}
+        }
+        Flow::Continue
+    }
+
+    /// Runs the search in a new thread, to keep reading the messages and receive its cancellation.
+    fn start_search(&mut self, id: &str, params: Option<&Spanned>) {
+        let config = match search_config(&self.root, params) {
+            Ok(config) => config,
+            Err(message) => {
+                send(&self.output, &error_response(id, INVALID_PARAMS, &message));
+                return;
+            }
+        };
+        let partial_result_token = params
+            .and_then(|params| field(params, "partialResultToken"))
+            .and_then(|token| match &token.value {
+                JsonValue::String(token) => Some(json::quote(token)),
+                JsonValue::Number(token) => Some(token.clone()),
+                _ => None,
+            });
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        self.searches
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(id.to_string(), Arc::clone(&is_cancelled));
+        let searches = Arc::clone(&self.searches);
+        let output = Arc::clone(&self.output);
+        let id = id.to_string();
+        thread::spawn(move || {
+            let result = search_workspace(
+                &config,
+                partial_result_token.as_deref(),
+                &is_cancelled,
+                &output,
+            );
+            searches
+                .lock()
+                .unwrap_or_else(|error| error.into_inner())
+                .remove(&id);
+            let message = match result {
+                Some(result) => response(&id, &result),
+                None => error_response(&id, REQUEST_CANCELLED, "request cancelled"),
+            };
+            send(&output, &message);
+        });
+    }
+}
+
+/// The first workspace folder, or the deprecated `rootUri` and `rootPath`.
+fn workspace_root(params: &Spanned) -> Option<PathBuf> {
+    let folder_uri = field(params, "workspaceFolders")
+        .and_then(|folders| folders.get(&[PathSegment::Index(0)]))
+        .and_then(|folder| field(folder, "uri"));
+    let root_uri = folder_uri.or_else(|| field(params, "rootUri"));
+    match root_uri.map(|uri| &uri.value) {
+        Some(JsonValue::String(uri)) => uri_path(uri),
+        _ => match field(params, "rootPath").map(|path| &path.value) {
+            Some(JsonValue::String(path)) => Some(PathBuf::from(path)),
+            _ => None,
+        },
+    }
+}
+
+/// Builds the `Config` of a `workspace/textSearch` request from its params `query`, `path`
+/// (relative to the workspace root), `ignoreCase`, `boolean` and `types`.
+fn search_config(root: &Path, params: Option<&Spanned>) -> Result<Config, String> {
+    let params = params.ok_or("missing params")?;
+    let string = |key: &str| match field(params, key).map(|value| &value.value) {
+        Some(JsonValue::String(value)) => Ok(Some(value.clone())),
+        None => Ok(None),
+        Some(_) => Err(format!("`{key}` should be a string")),
+    };
+    let flag = |key: &str| match field(params, key).map(|value| &value.value) {
+        Some(JsonValue::Bool(value)) => Ok(*value),
+        None => Ok(false),
+        Some(_) => Err(format!("`{key}` should be a boolean")),
+    };
+    let query = string("query")?.ok_or("missing `query`")?;
+    let path = match string("path")? {
+        Some(path) => root.join(path),
+        None => root.to_path_buf(),
+    };
+    let mut arguments = vec!["minigrep".to_string()];
+    if flag("boolean")? {
+        arguments.push("--boolean".to_string());
+    }
+    match field(params, "types").map(|types| &types.value) {
+        Some(JsonValue::Array(types)) => {
+            for file_type in types {
+                let JsonValue::String(file_type) = &file_type.value else {
+                    return Err("`types` should be an array of strings".to_string());
+                };
+                arguments.extend(["--type".to_string(), file_type.clone()]);
+            }
+        }
+        None => {}
+        Some(_) => return Err("`types` should be an array of strings".to_string()),
+    }
+    arguments.extend(["--".to_string(), query, path.display().to_string()]);
+    let mut config = Config::build(arguments.into_iter()).map_err(|error| {
+        let message = error.to_string();
+        message
+            .strip_prefix("Error: ")
+            .unwrap_or(&message)
+            .to_string()
+    })?;
+    config.ignore_case = flag("ignoreCase")?;
+    if !path.exists() {
+        return Err(format!("`{}` not found", path.display()));
+    }
+    Ok(config)
+}
+
+/// Searches the files and returns the result of the request, or `None` once cancelled.
+///
+/// With a partial result token, the matches of each file are sent in a `$/progress` notification
+/// and the result is empty, otherwise the result is every match. Unreadable and binary files are
+/// skipped.
+fn search_workspace(
+    config: &Config,
+    partial_result_token: Option<&str>,
+    is_cancelled: &AtomicBool,
+    output: &Output,
+) -> Option<String> {
+    let mut matches = Vec::new();
+    for file_path in walk::file_paths(Path::new(&config.file_path), &config.file_types, false) {
+        if is_cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let Ok(Some(file_lines)) = file_path.and_then(|file_path| read_lines(config, file_path))
+        else {
+            continue;
+        };
+        if file_lines.is_binary && config.binary_files == BinaryFiles::Binary {
+            continue;
+        }
+        let (file_lines, _, matching_items) = search_file_lines(config, file_lines);
+        let file_matches: Vec<String> = non_overlapping_items(&matching_items)
+            .into_iter()
+            .map(|item| match_json(&file_lines, item))
+            .collect();
+        match partial_result_token {
+            Some(token) if !file_matches.is_empty() => send(
+                output,
+                &format!(
+                    "{{\"jsonrpc\":\"2.0\",\"method\":\"$/progress\",\"params\":{{\"token\":{token},\"value\":[{}]}}}}",
+                    file_matches.join(",")
+                ),
+            ),
+            _ => matches.extend(file_matches),
+        }
+    }
+    Some(format!("[{}]", matches.join(",")))
+}
+
+/// `{"uri": …, "range": …, "text": …}`, with the line and the UTF-16 character positions of LSP.
+fn match_json(file_lines: &FileLines, item: &MatchingItem) -> String {
+    let text = &file_lines.lines[item.line_index];
+    let line = file_lines.line_indexes[item.line_index];
+    let position = |byte_index: usize| {
+        let character = text[..byte_index].encode_utf16().count();
+        format!("{{\"line\":{line},\"character\":{character}}}")
+    };
+    format!(
+        "{{\"uri\":{},\"range\":{{\"start\":{},\"end\":{}}},\"text\":{}}}",
+        json::quote(&file_uri(&file_lines.file_path)),
+        position(item.start_index),
+        position(item.end_index + 1),
+        json::quote(text)
+    )
+}
+
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let mut bytes = uri.strip_prefix("file://")?.bytes();
+    let mut path = Vec::new();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            path.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            path.push(byte);
+        }
+    }
+    String::from_utf8(path).ok().map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> (Server, Arc<Mutex<Vec<u8>>>) {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let server = Server {
+            root: PathBuf::from("tests/fixtures/project"),
+            searches: Arc::new(Mutex::new(HashMap::new())),
+            is_shut_down: false,
+            output: Arc::clone(&output) as Output,
+        };
+        (server, output)
+    }
+
+    #[test]
+    fn read_framed_messages() {
+        let mut input =
+            "Content-Length: 2\r\nContent-Type: x\r\n\r\n{}content-length: 4\r\n\r\nnull"
+                .as_bytes();
+        assert_eq!(read_message(&mut input).unwrap(), Some("{}".to_string()));
+        assert_eq!(read_message(&mut input).unwrap(), Some("null".to_string()));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+
+        let input = format!("Content-Length: {}\r\n\r\n", usize::MAX);
+        let error = read_message(&mut input.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn file_uri_round_trip() {
+        let path = Path::new("/home/me/my project/été.rs");
+        assert_eq!(
+            file_uri(path),
+            "file:///home/me/my%20project/%C3%A9t%C3%A9.rs"
+        );
+        assert_eq!(uri_path(&file_uri(path)).as_deref(), Some(path));
+        assert_eq!(uri_path("https://example.com"), None);
+    }
+
+    #[test]
+    fn match_positions_are_utf16() {
+        let file_lines = FileLines {
+            file_path: PathBuf::from("/notes.txt"),
+            line_indexes: vec![4],
+            lines: vec!["😀 frog".to_string()],
+            is_binary: false,
+            line_terminators: vec!["\n"],
+        };
+        let item = MatchingItem {
+            start_index: 5,
+            end_index: 8,
+            line_index: 0,
+        };
+        assert_eq!(
+            match_json(&file_lines, &item),
+            r#"{"uri":"file:///notes.txt","range":{"start":{"line":4,"character":3},"end":{"line":4,"character":7}},"text":"😀 frog"}"#
+        );
+    }
+
+    #[test]
+    fn cancelled_search_returns_nothing() {
+        let (server, output) = server();
+        let params = json::parse(r#"{"query": "engine"}"#).unwrap();
+        let config = search_config(&server.root, Some(&params)).unwrap();
+        let is_cancelled = AtomicBool::new(true);
+        assert_eq!(
+            search_workspace(&config, Some("1"), &is_cancelled, &server.output),
+            None
+        );
+        assert!(output.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn handle_requests() {
+        let (mut server, output) = server();
+        assert_eq!(
+            server.handle(r#"{"jsonrpc":"2.0","id":"a","method":"workspace/symbol"}"#),
+            Flow::Continue
+        );
+        assert_eq!(
+            server
+                .handle(r#"{"jsonrpc":"2.0","id":2,"method":"workspace/textSearch","params":{}}"#),
+            Flow::Continue
+        );
+        assert_eq!(
+            server.handle(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+            Flow::Exit
+        );
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.contains(r#"{"jsonrpc":"2.0","id":"a","error":{"code":-32601,"#));
+        assert!(output.contains(
+            r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"missing `query`"}}"#
+        ));
+    }
+}
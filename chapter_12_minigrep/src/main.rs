@@ -1,31 +1,21 @@
 use std::env;
-use std::io::ErrorKind;
 use std::process;
 
-use chapter_12_minigrep::config::Config;
+use chapter_12_minigrep::command::{self, Command};
 use chapter_12_minigrep::error::RunError;
 
 fn main() {
-    let config = Config::build(env::args()).unwrap_or_else(|error| {
-        eprintln!("{error}");
-        process::exit(1);
-    });
-    match chapter_12_minigrep::run(&config) {
-        Ok(_) => (),
-        Err(error) => match error {
-            RunError::InputOutputError(error) => {
-                match error.kind() {
-                    ErrorKind::NotFound => {
-                        eprintln!("Error: File `{}` not found.", config.file_path)
-                    }
-                    _ => eprintln!("Error: {error}"),
-                }
-                process::exit(1);
-            }
-            RunError::Other(error) => {
-                eprintln!("Error: {error}");
-                process::exit(1);
-            }
-        },
+    let result = Command::parse(env::args().skip(1))
+        .and_then(|(options, command)| command::run(&options, command));
+    if let Err(error) = result {
+        exit_with_error(&error);
     }
 }
+
+fn exit_with_error(error: &RunError) -> ! {
+    match error {
+        RunError::BadConfig(message) => eprintln!("{message}"),
+        _ => eprintln!("Error: {error}"),
+    }
+    process::exit(error.exit_code());
+}
@@ -0,0 +1,70 @@
+use crate::error::RunError;
+use crate::file_types::glob_matches;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// A command whose output is searched instead of the content of the files (`--pre`).
+#[derive(Debug, PartialEq)]
+pub struct Preprocessor {
+    pub command: String,
+    /// Globs of the names of the files to preprocess (`--pre-glob`), every file when empty.
+    pub globs: Vec<String>,
+}
+
+impl Preprocessor {
+    pub fn is_match(&self, file_path: &Path) -> bool {
+        let file_name = file_path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+        self.globs.is_empty() || self.globs.iter().any(|glob| glob_matches(glob, &file_name))
+    }
+
+    /// Runs the command with the file path as its only argument, and returns its standard output.
+    pub fn run(&self, file_path: &Path) -> Result<Vec<u8>, RunError> {
+        let output = Command::new(&self.command)
+            .arg(file_path)
+            .output()
+            .map_err(|error| {
+                RunError::InputOutputError(io::Error::new(
+                    error.kind(),
+                    format!("Cannot run the preprocessor `{}`: {error}", self.command),
+                ))
+            })?;
+        if !output.status.success() {
+            let mut message = format!(
+                "The preprocessor `{}` failed on `{}` ({}).",
+                self.command,
+                file_path.display(),
+                output.status
+            );
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                message.push_str(&format!(" {}", stderr.trim()));
+            }
+            return Err(RunError::InputOutputError(io::Error::other(message)));
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_file_names_with_globs() {
+        let preprocessor = Preprocessor {
+            command: "pdftotext".to_string(),
+            globs: vec!["*.pdf".to_string()],
+        };
+        assert!(preprocessor.is_match(Path::new("docs/manual.pdf")));
+        assert!(!preprocessor.is_match(Path::new("docs/manual.md")));
+        let preprocessor = Preprocessor {
+            globs: Vec::new(),
+            ..preprocessor
+        };
+        assert!(preprocessor.is_match(Path::new("docs/manual.md")));
+    }
+}
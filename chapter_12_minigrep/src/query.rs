@@ -0,0 +1,321 @@
+use crate::error::RunError;
+use crate::search::{search, search_case_insensitive, MatchingItem};
+
+#[derive(Debug, PartialEq)]
+pub enum Query {
+    Term(String),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    And,
+    Or,
+    Not,
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+impl Query {
+    /// Parses a boolean query, e.g. `foo AND (bar OR "baz qux") NOT quux`.
+    ///
+    /// `AND`, `OR` and `NOT` must be uppercase, `NOT` binds tighter than `AND` which binds
+    /// tighter than `OR`, and terms separated by spaces are implicitly joined with `AND`.
+    pub fn parse(query: &str) -> Result<Query, RunError> {
+        let bad_pattern = |reason: &str| RunError::BadPattern {
+            pattern: query.to_string(),
+            reason: reason.to_string(),
+        };
+        let tokens = tokenize(query).map_err(bad_pattern)?;
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            depth: 0,
+        };
+        let (ast, _) = parser.parse_or().map_err(bad_pattern)?;
+        if parser.index < parser.tokens.len() {
+            return Err(bad_pattern("unexpected `)`"));
+        }
+        Ok(ast)
+    }
+
+    pub fn is_match(&self, line: &str, ignore_case: bool) -> bool {
+        match self {
+            Query::Term(term) => {
+                if ignore_case {
+                    line.to_lowercase().contains(&term.to_lowercase())
+                } else {
+                    line.contains(term.as_str())
+                }
+            }
+            Query::Not(query) => !query.is_match(line, ignore_case),
+            Query::And(left, right) => {
+                left.is_match(line, ignore_case) && right.is_match(line, ignore_case)
+            }
+            Query::Or(left, right) => {
+                left.is_match(line, ignore_case) || right.is_match(line, ignore_case)
+            }
+        }
+    }
+
+    /// Terms that are not negated, the ones to highlight.
+    pub fn positive_terms(&self) -> Vec<&str> {
+        let mut terms = Vec::new();
+        self.collect_terms(false, &mut terms);
+        terms
+    }
+
+    fn collect_terms<'a>(&'a self, is_negated: bool, terms: &mut Vec<&'a str>) {
+        match self {
+            Query::Term(term) => {
+                if !is_negated {
+                    terms.push(term);
+                }
+            }
+            Query::Not(query) => query.collect_terms(!is_negated, terms),
+            Query::And(left, right) | Query::Or(left, right) => {
+                left.collect_terms(is_negated, terms);
+                right.collect_terms(is_negated, terms);
+            }
+        }
+    }
+
+    /// Returns the indexes of the matching lines, and the non-overlapping matches of the positive
+    /// terms in these lines.
+    pub fn search(&self, lines: &[String], ignore_case: bool) -> (Vec<usize>, Vec<MatchingItem>) {
+        let positive_terms = self.positive_terms();
+        let mut matching_line_indexes = Vec::new();
+        let mut matching_items: Vec<MatchingItem> = Vec::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            if !self.is_match(line, ignore_case) {
+                continue;
+            }
+            matching_line_indexes.push(line_index);
+            let line = [line.to_string()];
+            let mut line_items: Vec<MatchingItem> = positive_terms
+                .iter()
+                .flat_map(|term| {
+                    if ignore_case {
+                        search_case_insensitive(term, &line)
+                    } else {
+                        search(term, &line)
+                    }
+                })
+                .collect();
+            line_items.sort_by_key(|item| (item.start_index, item.end_index));
+            for item in line_items {
+                let is_overlapping = matching_items.last().is_some_and(|last| {
+                    last.line_index == line_index && last.end_index >= item.start_index
+                });
+                if !is_overlapping {
+                    matching_items.push(MatchingItem { line_index, ..item });
+                }
+            }
+        }
+        (matching_line_indexes, matching_items)
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut characters = query.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '(' => tokens.push(Token::OpenParenthesis),
+            ')' => tokens.push(Token::CloseParenthesis),
+            '"' => {
+                let mut phrase = String::new();
+                loop {
+                    match characters.next() {
+                        Some('"') => break,
+                        Some('\\') => match characters.next() {
+                            Some(escaped) => phrase.push(escaped),
+                            None => return Err("unclosed quoted phrase"),
+                        },
+                        Some(character) => phrase.push(character),
+                        None => return Err("unclosed quoted phrase"),
+                    }
+                }
+                tokens.push(Token::Phrase(phrase));
+            }
+            _ if character.is_whitespace() => {}
+            _ => {
+                let mut word = character.to_string();
+                while let Some(character) = characters
+                    .next_if(|character| !character.is_whitespace() && !"()\"".contains(*character))
+                {
+                    word.push(character);
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Depth of the nested operators and parentheses past which a query is not parsed, so that
+/// deeply nested queries cannot overflow the stack of the recursive parser and matcher.
+const MAX_DEPTH: usize = 128;
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    depth: usize,
+}
+
+/// The parsed query with its depth, the number of operators on its longest branch.
+type Parsed = Result<(Query, usize), &'static str>;
+
+impl Parser {
+    fn parse_or(&mut self) -> Parsed {
+        let (mut query, mut depth) = self.parse_and()?;
+        while self.tokens.get(self.index) == Some(&Token::Or) {
+            self.index += 1;
+            let (right, right_depth) = self.parse_and()?;
+            depth = operator_depth(depth.max(right_depth))?;
+            query = Query::Or(Box::new(query), Box::new(right));
+        }
+        Ok((query, depth))
+    }
+
+    fn parse_and(&mut self) -> Parsed {
+        let (mut query, mut depth) = self.parse_not()?;
+        loop {
+            match self.tokens.get(self.index) {
+                Some(Token::And) => self.index += 1,
+                Some(Token::Or | Token::CloseParenthesis) | None => return Ok((query, depth)),
+                Some(_) => {}
+            }
+            let (right, right_depth) = self.parse_not()?;
+            depth = operator_depth(depth.max(right_depth))?;
+            query = Query::And(Box::new(query), Box::new(right));
+        }
+    }
+
+    fn parse_not(&mut self) -> Parsed {
+        if self.tokens.get(self.index) == Some(&Token::Not) {
+            self.index += 1;
+            let (query, depth) = self.nested(Self::parse_not)?;
+            return Ok((Query::Not(Box::new(query)), operator_depth(depth)?));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Parsed {
+        let token = self
+            .tokens
+            .get(self.index)
+            .ok_or("unexpected end of query")?;
+        self.index += 1;
+        match token {
+            Token::Word(term) | Token::Phrase(term) if term.is_empty() => {
+                Err("empty quoted phrase")
+            }
+            Token::Word(term) | Token::Phrase(term) => Ok((Query::Term(term.clone()), 0)),
+            Token::OpenParenthesis => {
+                let query = self.nested(Self::parse_or)?;
+                if self.tokens.get(self.index) != Some(&Token::CloseParenthesis) {
+                    return Err("missing `)`");
+                }
+                self.index += 1;
+                Ok(query)
+            }
+            Token::CloseParenthesis => Err("unexpected `)`"),
+            Token::And | Token::Or | Token::Not => Err("expected a term"),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Parsed) -> Parsed {
+        if self.depth == MAX_DEPTH {
+            return Err(TOO_DEEP);
+        }
+        self.depth += 1;
+        let query = parse(self);
+        self.depth -= 1;
+        query
+    }
+}
+
+const TOO_DEEP: &str = "query nested too deeply";
+
+/// Depth of an operator over operands of at most `depth`.
+fn operator_depth(depth: usize) -> Result<usize, &'static str> {
+    if depth == MAX_DEPTH {
+        Err(TOO_DEEP)
+    } else {
+        Ok(depth + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term: &str) -> Box<Query> {
+        Box::new(Query::Term(term.to_string()))
+    }
+
+    #[test]
+    fn parse_precedence() {
+        assert_eq!(
+            Query::parse(r#"foo bar OR NOT "baz qux""#).unwrap(),
+            Query::Or(
+                Box::new(Query::And(term("foo"), term("bar"))),
+                Box::new(Query::Not(term("baz qux"))),
+            )
+        );
+        assert_eq!(
+            Query::parse("foo AND (bar OR baz)").unwrap(),
+            Query::And(term("foo"), Box::new(Query::Or(term("bar"), term("baz"))))
+        );
+        assert!(Query::parse("foo AND").is_err());
+        assert!(Query::parse("(foo").is_err());
+        assert!(Query::parse("foo)").is_err());
+        assert!(Query::parse("\"foo").is_err());
+    }
+
+    #[test]
+    fn parse_nesting_up_to_max_depth() {
+        let nested = |depth: usize| format!("{}foo{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Query::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(Query::parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(Query::parse(&"(".repeat(200_000)).is_err());
+        assert!(Query::parse(&format!("{}foo", "NOT ".repeat(MAX_DEPTH))).is_ok());
+        assert!(Query::parse(&format!("{}foo", "NOT ".repeat(MAX_DEPTH + 1))).is_err());
+        assert!(Query::parse(&"foo ".repeat(MAX_DEPTH + 1)).is_ok());
+        assert!(Query::parse(&"foo ".repeat(MAX_DEPTH + 2)).is_err());
+        assert!(Query::parse(&format!("{}foo", "foo OR ".repeat(100_000))).is_err());
+    }
+
+    #[test]
+    fn search_highlights_positive_terms() {
+        let query = Query::parse("us AND NOT banish").unwrap();
+        let lines = [
+            "Then there's a pair of us - don't tell!".to_string(),
+            "They'd banish us, you know.".to_string(),
+        ];
+        assert_eq!(query.positive_terms(), vec!["us"]);
+        let (line_indexes, matching_items) = query.search(&lines, false);
+        assert_eq!(line_indexes, vec![0]);
+        assert_eq!(
+            matching_items,
+            vec![MatchingItem {
+                start_index: 23,
+                end_index: 24,
+                line_index: 0,
+            }]
+        );
+
+        let query = Query::parse("NOT pair").unwrap();
+        assert_eq!(query.search(&lines, false), (vec![1], vec![]));
+    }
+}
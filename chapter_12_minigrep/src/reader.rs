@@ -0,0 +1,214 @@
+use crate::binary::{self, BinaryFiles};
+use crate::config::Config;
+use crate::error::RunError;
+use crate::terminator::LineTerminator;
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Files of at least this size (in bytes) are memory-mapped, unless `--mmap`/`--no-mmap` is used.
+pub const MMAP_THRESHOLD: u64 = 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub struct FileLines {
+    pub file_path: PathBuf,
+    /// Index in the file of each line of `lines`.
+    pub line_indexes: Vec<usize>,
+    pub lines: Vec<String>,
+    /// The file contains a NUL byte in its first block, its invalid UTF-8 is replaced with `�`.
+    pub is_binary: bool,
+    /// Printed after each line of `lines`, see `LineTerminator::trim`.
+    pub line_terminators: Vec<&'static str>,
+}
+
+pub fn read_file(file_path: PathBuf) -> Result<(PathBuf, String), RunError> {
+    match fs::read_to_string(&file_path) {
+        Ok(file_content) => Ok((file_path, file_content)),
+        Err(error) => Err(RunError::from_io_error(error, &file_path)),
+    }
+}
+
+/// Reads the lines of the file that may match the query.
+///
+/// When the file is memory-mapped, the lines are searched directly in the mapped bytes and only
+/// the lines containing the query are copied, otherwise every line of the file is returned.
+///
+/// The output of the `--pre` command is read instead of the files it matches, and binary files
+/// are skipped (`None`) with `--binary-files=without-match`.
+pub fn read_lines(config: &Config, file_path: PathBuf) -> Result<Option<FileLines>, RunError> {
+    if let Some(preprocessor) = &config.preprocessor {
+        if preprocessor.is_match(&file_path) {
+            let output = preprocessor.run(&file_path)?;
+            return read_lines_from_bytes(config, file_path, output);
+        }
+    }
+    // NUL bytes separate the lines of `--null-data` files, they are not a sign of binary data.
+    let is_binary = config.line_terminator != LineTerminator::Nul
+        && binary::is_binary_file(&file_path)
+            .map_err(|error| RunError::from_io_error(error, &file_path))?;
+    if is_binary {
+        if config.binary_files == BinaryFiles::WithoutMatch {
+            return Ok(None);
+        }
+        let file_content =
+            fs::read(&file_path).map_err(|error| RunError::from_io_error(error, &file_path))?;
+        return read_lines_from_bytes(config, file_path, file_content);
+    }
+    let use_mmap = match config.mmap {
+        // Fields need the header and the lines of multiline records, the scope needs every line
+        // to find the comments and strings, `--passthru` prints every line and the query of
+        // `--interactive` changes, not only the matching lines.
+        _ if config.fields.is_some()
+            || config.scope.is_some()
+            || config.passthru
+            || config.interactive =>
+        {
+            false
+        }
+        Some(mmap) => mmap,
+        None => fs::metadata(&file_path)
+            .map(|metadata| metadata.is_file() && metadata.len() >= MMAP_THRESHOLD)
+            .unwrap_or(false),
+    };
+    if !use_mmap {
+        let (file_path, file_content) = read_file(file_path)?;
+        return Ok(Some(split_lines(
+            config,
+            file_path,
+            &file_content,
+            is_binary,
+        )));
+    }
+    let query_lowercase = config.query.to_lowercase();
+    let is_candidate = |line: &str| match &config.boolean_query {
+        Some(query) => query.is_match(line, config.ignore_case),
+        None if config.ignore_case => line.to_lowercase().contains(&query_lowercase),
+        None => line.contains(&config.query),
+    };
+    let lines = read_lines_mmap(&file_path, config.line_terminator, is_candidate)
+        .map_err(|error| RunError::from_io_error(error, &file_path))?;
+    let mut file_lines = FileLines {
+        file_path,
+        line_indexes: Vec::new(),
+        lines: Vec::new(),
+        is_binary,
+        line_terminators: Vec::new(),
+    };
+    for (line_index, line, line_terminator) in lines {
+        file_lines.line_indexes.push(line_index);
+        file_lines.lines.push(line);
+        file_lines.line_terminators.push(line_terminator);
+    }
+    Ok(Some(file_lines))
+}
+
+/// Splits the content of a file already in memory, like a member of an archive, into lines.
+pub fn read_lines_from_bytes(
+    config: &Config,
+    file_path: PathBuf,
+    file_content: Vec<u8>,
+) -> Result<Option<FileLines>, RunError> {
+    let is_binary =
+        config.line_terminator != LineTerminator::Nul && binary::is_binary(&file_content);
+    if is_binary && config.binary_files == BinaryFiles::WithoutMatch {
+        return Ok(None);
+    }
+    let file_content = match String::from_utf8(file_content) {
+        Ok(file_content) => file_content,
+        Err(error) if is_binary => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+        Err(error) => {
+            let source = io::Error::new(ErrorKind::InvalidData, error);
+            return Err(RunError::from_io_error(source, &file_path));
+        }
+    };
+    Ok(Some(split_lines(
+        config,
+        file_path,
+        &file_content,
+        is_binary,
+    )))
+}
+
+fn split_lines(
+    config: &Config,
+    file_path: PathBuf,
+    file_content: &str,
+    is_binary: bool,
+) -> FileLines {
+    let (lines, line_terminators): (Vec<String>, _) = config
+        .line_terminator
+        .lines(file_content)
+        .map(|(line, line_terminator)| (line.to_string(), line_terminator))
+        .unzip();
+    FileLines {
+        file_path,
+        line_indexes: (0..lines.len()).collect(),
+        lines,
+        is_binary,
+        line_terminators,
+    }
+}
+
+/// Returns the candidate lines with their index and the terminator to print after them.
+fn read_lines_mmap(
+    file_path: &Path,
+    line_terminator: LineTerminator,
+    is_candidate: impl Fn(&str) -> bool,
+) -> io::Result<Vec<(usize, String, &'static str)>> {
+    let file = File::open(file_path)?;
+    if file.metadata()?.is_dir() {
+        return Err(io::Error::from(ErrorKind::IsADirectory));
+    }
+    // SAFETY: the file is only read. Like other grep tools, we accept that modifying or truncating
+    // the file while it is searched is undefined behavior (usually a `SIGBUS` crash).
+    let mmap = unsafe { Mmap::map(&file)? };
+    let file_content = std::str::from_utf8(&mmap)
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+    let lines = line_terminator
+        .lines(file_content)
+        .enumerate()
+        .filter(|(_, (line, _))| is_candidate(line))
+        .map(|(line_index, (line, line_terminator))| {
+            (line_index, line.to_string(), line_terminator)
+        })
+        .collect();
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mmap_only_materializes_matching_lines() {
+        let lines = read_lines_mmap(Path::new("poem.txt"), LineTerminator::Lf, |line| {
+            line.contains("the")
+        })
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (
+                    2,
+                    "Then there's a pair of us - don't tell!".to_string(),
+                    "\n"
+                ),
+                (7, "To tell your name the livelong day".to_string(), "\n"),
+            ]
+        );
+        let lines = read_lines_mmap(
+            Path::new("tests/fixtures/line_endings/crlf.txt"),
+            LineTerminator::Crlf,
+            |line| line.starts_with("To"),
+        )
+        .unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                (1, "To tell your name the livelong day".to_string(), "\r\n"),
+                (2, "To an admiring bog!".to_string(), "\r\n"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::ops::Range;
+
+/// Part of Rust source code searched with `--scope`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Scope {
+    /// Everything but the comments and the string and character literals.
+    Code,
+    Comments,
+    /// String, raw string and character literals, with their quotes.
+    Strings,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Region {
+    Comment,
+    String,
+}
+
+impl Scope {
+    pub fn parse(value: &str) -> Result<Scope, String> {
+        match value {
+            "code" => Ok(Scope::Code),
+            "comments" => Ok(Scope::Comments),
+            "strings" => Ok(Scope::Strings),
+            _ => Err(format!(
+                "Error: Unknown scope `{value}`, expected one of: code, comments, strings."
+            )),
+        }
+    }
+
+    /// `region` is `None` for code.
+    fn contains(self, region: Option<Region>) -> bool {
+        matches!(
+            (self, region),
+            (Scope::Code, None)
+                | (Scope::Comments, Some(Region::Comment))
+                | (Scope::Strings, Some(Region::String))
+        )
+    }
+}
+
+/// Returns the lines with the text outside of `scope` replaced by spaces, byte for byte, so that
+/// the matches found in the masked lines have the same byte ranges in the original lines.
+///
+/// The lines are lexed together since comments and strings can span several lines.
+pub fn mask(lines: &[String], scope: Scope) -> Vec<String> {
+    let text = lines.join("\n");
+    let mut masked = text.clone().into_bytes();
+    let mut blank = |range: Range<usize>| {
+        for byte in &mut masked[range] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    };
+    let mut code_start = 0;
+    for (range, region) in regions(&text) {
+        if !scope.contains(None) {
+            blank(code_start..range.start);
+        }
+        if !scope.contains(Some(region)) {
+            blank(range.clone());
+        }
+        code_start = range.end;
+    }
+    if !scope.contains(None) {
+        blank(code_start..text.len());
+    }
+    // Whole characters are blanked, so the masked text is still valid UTF-8.
+    let masked = String::from_utf8(masked).expect("Masked text should be valid UTF-8.");
+    let mut line_start = 0;
+    lines
+        .iter()
+        .map(|line| {
+            let masked_line = masked[line_start..line_start + line.len()].to_string();
+            line_start += line.len() + 1;
+            masked_line
+        })
+        .collect()
+}
+
+/// Byte ranges of the comments (`//`, nested `/* */`) and of the string (`"..."`, `r#"..."#`)
+/// and character (`'a'`, `'\n'`) literals of Rust source code. Lifetimes are code.
+pub fn regions(text: &str) -> Vec<(Range<usize>, Region)> {
+    let bytes = text.as_bytes();
+    let mut regions = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let start_index = index;
+        let rest = &bytes[index..];
+        let region = if rest.starts_with(b"//") {
+            index = find(bytes, index, b'\n').unwrap_or(bytes.len());
+            Region::Comment
+        } else if rest.starts_with(b"/*") {
+            index = block_comment_end(bytes, index);
+            Region::Comment
+        } else if let Some(hashes_count) = raw_string_hashes_count(bytes, index) {
+            index = raw_string_end(bytes, index, hashes_count);
+            Region::String
+        } else if bytes[index] == b'"' {
+            index = string_end(bytes, index + 1);
+            Region::String
+        } else if let Some(end_index) = character_end(text, index) {
+            index = end_index;
+            Region::String
+        } else {
+            index += 1;
+            continue;
+        };
+        regions.push((start_index..index, region));
+    }
+    regions
+}
+
+fn find(bytes: &[u8], from_index: usize, byte: u8) -> Option<usize> {
+    bytes[from_index..]
+        .iter()
+        .position(|candidate| *candidate == byte)
+        .map(|position| from_index + position)
+}
+
+fn block_comment_end(bytes: &[u8], start_index: usize) -> usize {
+    let mut depth = 0;
+    let mut index = start_index;
+    while index < bytes.len() {
+        if bytes[index..].starts_with(b"/*") {
+            depth += 1;
+            index += 2;
+        } else if bytes[index..].starts_with(b"*/") {
+            depth -= 1;
+            index += 2;
+            if depth == 0 {
+                return index;
+            }
+        } else {
+            index += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// End of a string whose content starts at `index`, after its closing quote.
+fn string_end(bytes: &[u8], mut index: usize) -> usize {
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'"' => return index + 1,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Number of `#` of a raw string (`r"`, `r#"`, `br#"`...) starting at `index`.
+fn raw_string_hashes_count(bytes: &[u8], index: usize) -> Option<usize> {
+    let is_identifier = |index: usize| {
+        index > 0 && (bytes[index - 1].is_ascii_alphanumeric() || bytes[index - 1] == b'_')
+    };
+    let is_prefixed = match index.checked_sub(1).map(|previous| bytes[previous]) {
+        Some(b'b' | b'c') => !is_identifier(index - 1),
+        _ => !is_identifier(index),
+    };
+    if bytes[index] != b'r' || !is_prefixed {
+        return None;
+    }
+    let hashes_count = bytes[index + 1..]
+        .iter()
+        .take_while(|byte| **byte == b'#')
+        .count();
+    (bytes.get(index + 1 + hashes_count) == Some(&b'"')).then_some(hashes_count)
+}
+
+fn raw_string_end(bytes: &[u8], start_index: usize, hashes_count: usize) -> usize {
+    let mut index = start_index + hashes_count + 2;
+    while let Some(quote_index) = find(bytes, index, b'"') {
+        let closing_end_index = quote_index + 1 + hashes_count;
+        if bytes.len() >= closing_end_index
+            && bytes[quote_index + 1..closing_end_index]
+                .iter()
+                .all(|byte| *byte == b'#')
+        {
+            return closing_end_index;
+        }
+        index = quote_index + 1;
+    }
+    bytes.len()
+}
+
+/// End of a character literal starting at `index`, `None` for a lifetime or a label like `'a`.
+fn character_end(text: &str, index: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    if bytes[index] != b'\'' {
+        return None;
+    }
+    if bytes.get(index + 1) == Some(&b'\\') {
+        // `'\''`, `'\n'` or `'\u{1F600}'`, on a single line.
+        let closing_index = (index + 3..bytes.len())
+            .take_while(|index| bytes[*index] != b'\n')
+            .find(|index| bytes[*index] == b'\'')?;
+        return Some(closing_index + 1);
+    }
+    let character = text[index + 1..].chars().next()?;
+    let closing_index = index + 1 + character.len_utf8();
+    (character != '\'' && character != '\n' && bytes.get(closing_index) == Some(&b'\''))
+        .then_some(closing_index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masked(text: &str, scope: Scope) -> String {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        mask(&lines, scope).join("\n")
+    }
+
+    #[test]
+    fn lex_comments_and_literals() {
+        let text = "let a = \"// no\"; // yes \"no\"\n/* outer /* inner */ still */ 'x' '\\'' 'a: r#\"say \"hi\"\"#";
+        let region_texts: Vec<(&str, Region)> = regions(text)
+            .into_iter()
+            .map(|(range, region)| (&text[range], region))
+            .collect();
+        assert_eq!(
+            region_texts,
+            vec![
+                ("\"// no\"", Region::String),
+                ("// yes \"no\"", Region::Comment),
+                ("/* outer /* inner */ still */", Region::Comment),
+                ("'x'", Region::String),
+                ("'\\''", Region::String),
+                ("r#\"say \"hi\"\"#", Region::String),
+            ]
+        );
+    }
+
+    #[test]
+    fn mask_outside_of_scope() {
+        let text = "let s = \"été\"; /* frog\nbog */ let c = 'é';";
+        assert_eq!(
+            masked(text, Scope::Code),
+            "let s =        ;        \n       let c =     ;"
+        );
+        assert_eq!(
+            masked(text, Scope::Comments),
+            "                 /* frog\nbog */              "
+        );
+        assert_eq!(
+            masked(text, Scope::Strings),
+            "        \"été\"         \n               'é' "
+        );
+        // Identifiers ending with `r` do not start raw strings.
+        assert_eq!(regions("bar\"x\"")[0].0, 3..6);
+    }
+}
@@ -1,3 +1,5 @@
+use std::iter;
+
 #[derive(Debug, PartialEq)]
 pub struct MatchingItem {
     pub start_index: usize,
@@ -60,13 +62,46 @@ pub fn search(query: &str, lines: &[String]) -> Vec<MatchingItem> {
 //     matching_items
 // }
 
+/// The matches are found in the lowercased lines, their indexes are those of the original lines
+/// since lowercasing can change the length of a character, e.g. `İ` is 2 bytes and `i̇` is 3.
 pub fn search_case_insensitive(query: &str, lines: &[String]) -> Vec<MatchingItem> {
-    let query_lowercase = query.to_lowercase();
+    let (query_lowercase, _) = lowercase(query);
     let mut lines_lowercase: Vec<String> = Vec::new();
+    let mut original_indexes: Vec<Vec<usize>> = Vec::new();
     for line in lines {
-        lines_lowercase.push(line.to_lowercase());
+        let (line_lowercase, indexes) = lowercase(line);
+        lines_lowercase.push(line_lowercase);
+        original_indexes.push(indexes);
     }
     search(&query_lowercase, &lines_lowercase)
+        .into_iter()
+        .map(|item| {
+            let line = &lines[item.line_index];
+            let indexes = &original_indexes[item.line_index];
+            // A match ending inside the lowercase of a character ends after this character.
+            let last_index = indexes[item.end_index];
+            let last_character_len = line[last_index..].chars().next().map_or(1, char::len_utf8);
+            MatchingItem {
+                start_index: indexes[item.start_index],
+                end_index: last_index + last_character_len - 1,
+                line_index: item.line_index,
+            }
+        })
+        .collect()
+}
+
+/// Lowercases `text` character by character, with the index in `text` of the character each
+/// byte of the lowercased text comes from.
+fn lowercase(text: &str) -> (String, Vec<usize>) {
+    let mut text_lowercase = String::new();
+    let mut original_indexes = Vec::new();
+    for (index, character) in text.char_indices() {
+        for character_lowercase in character.to_lowercase() {
+            text_lowercase.push(character_lowercase);
+            original_indexes.extend(iter::repeat_n(index, character_lowercase.len_utf8()));
+        }
+    }
+    (text_lowercase, original_indexes)
 }
 
 #[cfg(test)]
@@ -118,4 +153,15 @@ mod tests {
         assert_eq!("Rust", lines[0].get(0..=3).unwrap());
         assert_eq!("rust", lines[3].get(1..=4).unwrap());
     }
+
+    #[test]
+    fn case_insensitive_with_length_changing_characters() {
+        // `ẞ` lowercases to `ß`, 3 bytes to 2, and `İ` to `i̇`, 2 bytes to 3.
+        let lines = ["ẞx".to_string(), "İx y".to_string()];
+        let items = search_case_insensitive("X", &lines);
+        assert_eq!(&lines[0][items[0].start_index..=items[0].end_index], "x");
+        assert_eq!(&lines[1][items[1].start_index..=items[1].end_index], "x");
+        let items = search_case_insensitive("i", &lines);
+        assert_eq!(&lines[1][items[0].start_index..=items[0].end_index], "İ");
+    }
 }
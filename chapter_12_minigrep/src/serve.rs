@@ -0,0 +1,503 @@
+use crate::arguments::{self, Argument, ArgumentValue, ParsedArgument, SERVE_ARGUMENTS};
+use crate::binary::BinaryFiles;
+use crate::config::Config;
+use crate::error::RunError;
+use crate::json;
+use crate::reader::{read_lines, FileLines};
+use crate::search::MatchingItem;
+use crate::{non_overlapping_items, search_file_lines, walk};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest accepted request line and headers, in bytes.
+const MAX_REQUEST_SIZE: u64 = 8 * 1024;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Options of `Config::build` accepted as query parameters of `/search`, in addition to `query`,
+/// `path` and `ignore_case`. A flag is set with the value `true`.
+const SEARCH_PARAMETERS: [&str; 14] = [
+    "type",
+    "type-not",
+    "type-add",
+    "no-messages",
+    "mmap",
+    "no-mmap",
+    "text",
+    "binary-files",
+    "lines",
+    "field",
+    "delimiter",
+    "json-path",
+    "where",
+    "boolean",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct ServeConfig {
+    /// Canonical path of the directory that can be searched.
+    pub root: PathBuf,
+    /// `0` to use any free port.
+    pub port: u16,
+    /// Maximum time to receive a request and to send its response.
+    pub timeout: Duration,
+}
+
+impl ServeConfig {
+    /// Parses the arguments following `minigrep serve`.
+    pub fn build(arguments: impl Iterator<Item = String>) -> Result<ServeConfig, RunError> {
+        let error_message =
+            "Usage: minigrep serve --root <directory> --port <port> [--timeout <seconds>]";
+        let usage_error = || RunError::BadConfig(error_message.to_string());
+        let mut root = None;
+        let mut port = None;
+        let mut timeout = DEFAULT_TIMEOUT;
+        for parsed_argument in arguments::parse(&SERVE_ARGUMENTS, arguments, usage_error)? {
+            let ParsedArgument::Option(definition, value) = parsed_argument else {
+                return Err(usage_error());
+            };
+            match definition.long {
+                "root" => root = Some(PathBuf::from(value)),
+                "port" => match value.parse::<u16>() {
+                    Ok(number) => port = Some(number),
+                    Err(_) => {
+                        return Err(RunError::BadConfig(format!(
+                            "Error: Invalid port `{value}`."
+                        )))
+                    }
+                },
+                "timeout" => match value.parse::<u64>() {
+                    Ok(seconds) if seconds > 0 => timeout = Duration::from_secs(seconds),
+                    _ => {
+                        return Err(RunError::BadConfig(format!(
+                        "Error: Invalid timeout `{value}`, expected a positive number of seconds."
+                    )))
+                    }
+                },
+                long => unreachable!("`--{long}` is defined but not handled"),
+            }
+        }
+        let (Some(root), Some(port)) = (root, port) else {
+            return Err(usage_error());
+        };
+        let root = root
+            .canonicalize()
+            .map_err(|error| RunError::from_io_error(error, &root))?;
+        if !root.is_dir() {
+            return Err(RunError::BadConfig(format!(
+                "Error: `{}` is not a directory.",
+                root.display()
+            )));
+        }
+        Ok(ServeConfig {
+            root,
+            port,
+            timeout,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Response {
+        Response {
+            status,
+            body: format!("{{\"error\":{}}}", json::quote(message)),
+        }
+    }
+
+    /// Paths in error messages are made relative to the root, to not disclose where it is.
+    fn from_run_error(error: &RunError, root: &Path) -> Response {
+        let status = match error {
+            RunError::BadConfig(_)
+            | RunError::BadPattern { .. }
+            | RunError::IsADirectory { .. } => 400,
+            RunError::PermissionDenied { .. } => 403,
+            RunError::FileNotFound { .. } => 404,
+            RunError::InvalidUtf8 { .. } => 422,
+            RunError::InputOutputError(_) | RunError::Other(_) => 500,
+        };
+        let message = error
+            .to_string()
+            .replace(&format!("{}/", root.display()), "");
+        Response::error(status, message.strip_prefix("Error: ").unwrap_or(&message))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            422 => "Unprocessable Content",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Serves `GET /search` on `127.0.0.1`, each connection is handled by a new thread.
+pub fn run(config: &ServeConfig) -> Result<(), RunError> {
+    let listener = TcpListener::bind(("127.0.0.1", config.port))?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let root = config.root.clone();
+        let timeout = config.timeout;
+        thread::spawn(move || handle_connection(stream, &root, timeout));
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, root: &Path, timeout: Duration) {
+    let response = match read_request(&stream, timeout) {
+        Ok((method, target)) => respond(root, &method, &target),
+        Err(response) => response,
+    };
+    // The client may be gone, there is nobody to report the error to.
+    let _ = stream
+        .set_write_timeout(Some(timeout))
+        .and_then(|_| write_response(&mut stream, &response));
+}
+
+/// Reads the request line and the headers before the deadline, and returns the method and the
+/// target of the request line. The body of the request, if any, is ignored.
+fn read_request(stream: &TcpStream, timeout: Duration) -> Result<(String, String), Response> {
+    let deadline = Instant::now() + timeout;
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_SIZE));
+    let mut request_line = String::new();
+    let mut line = String::new();
+    loop {
+        let remaining_time = deadline.saturating_duration_since(Instant::now());
+        if remaining_time.is_zero() {
+            return Err(Response::error(408, "request timeout"));
+        }
+        if stream.set_read_timeout(Some(remaining_time)).is_err() {
+            return Err(Response::error(500, "cannot set the request timeout"));
+        }
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) if reader.get_ref().limit() == 0 => {
+                return Err(Response::error(431, "request too large"))
+            }
+            Ok(0) => return Err(Response::error(400, "incomplete request")),
+            Ok(_) => {}
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Err(Response::error(408, "request timeout"))
+            }
+            Err(_) => return Err(Response::error(400, "invalid request")),
+        }
+        if !line.ends_with('\n') {
+            continue;
+        }
+        if request_line.is_empty() {
+            request_line = line.trim_end().to_string();
+        } else if line.trim_end().is_empty() {
+            break;
+        }
+    }
+    let mut parts = request_line.split(' ');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version), None) if version.starts_with("HTTP/1.") => {
+            Ok((method.to_string(), target.to_string()))
+        }
+        _ => Err(Response::error(400, "invalid request line")),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn respond(root: &Path, method: &str, target: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, "only GET requests are supported");
+    }
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/search" {
+        return Response::error(404, "not found, the search is at /search");
+    }
+    let result = parse_query_string(query_string)
+        .map_err(|message| Response::error(400, &message))
+        .and_then(|parameters| build_config(root, &parameters))
+        .and_then(|config| search(root, &config));
+    match result {
+        Ok(body) => Response { status: 200, body },
+        Err(response) => response,
+    }
+}
+
+/// Decodes the `name=value` pairs of a query string, in order.
+fn parse_query_string(query_string: &str) -> Result<Vec<(String, String)>, String> {
+    query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((percent_decode(name)?, percent_decode(value)?))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> Result<String, String> {
+    let error = || format!("invalid percent-encoding in `{text}`");
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut characters = text.bytes();
+    while let Some(byte) = characters.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [
+                    characters.next().ok_or_else(error)?,
+                    characters.next().ok_or_else(error)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| error())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| error())?);
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| error())
+}
+
+/// Builds the `Config` of a search from the query parameters, like the command line
+/// `minigrep [options] -- <query> <root/path>`.
+fn build_config(root: &Path, parameters: &[(String, String)]) -> Result<Config, Response> {
+    let mut arguments = vec!["minigrep".to_string()];
+    let mut query = None;
+    let mut path = None;
+    let mut ignore_case = false;
+    let parse_flag = |name: &str, value: &str| match value {
+        "true" | "" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(Response::error(
+            400,
+            &format!("invalid value `{value}` of `{name}`, expected true or false"),
+        )),
+    };
+    for (name, value) in parameters {
+        match name.as_str() {
+            "query" => query = Some(value.clone()),
+            "path" => path = Some(value.clone()),
+            "ignore_case" => ignore_case = parse_flag(name, value)?,
+            name if SEARCH_PARAMETERS.contains(&name) => {
+                let option = format!("--{name}");
+                let definition =
+                    Argument::find(&option).expect("search parameters should be arguments");
+                if definition.value != ArgumentValue::None {
+                    arguments.extend([option, value.clone()]);
+                } else if parse_flag(name, value)? {
+                    arguments.push(option);
+                }
+            }
+            name => return Err(Response::error(400, &format!("unknown parameter `{name}`"))),
+        }
+    }
+    let query = query.ok_or_else(|| Response::error(400, "missing parameter `query`"))?;
+    let path = resolve_path(root, path.as_deref().unwrap_or("."))?;
+    arguments.extend(["--".to_string(), query, path.display().to_string()]);
+    let mut config = Config::build(arguments.into_iter())
+        .map_err(|error| Response::from_run_error(&error, root))?;
+    config.ignore_case = ignore_case;
+    Ok(config)
+}
+
+/// Resolves a path relative to the root, refusing the paths going out of the root with `..`, an
+/// absolute path or a symbolic link.
+fn resolve_path(root: &Path, path: &str) -> Result<PathBuf, Response> {
+    let relative_path = Path::new(path);
+    let forbidden =
+        || Response::error(403, &format!("`{path}` is outside of the served directory"));
+    let is_relative = relative_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_relative {
+        return Err(forbidden());
+    }
+    let resolved_path = root.join(relative_path).canonicalize().map_err(|error| {
+        Response::from_run_error(&RunError::from_io_error(error, relative_path), root)
+    })?;
+    if resolved_path.starts_with(root) {
+        Ok(resolved_path)
+    } else {
+        Err(forbidden())
+    }
+}
+
+fn search(root: &Path, config: &Config) -> Result<String, Response> {
+    let mut files_searched = 0;
+    let mut files = Vec::new();
+    for file_path in walk::file_paths(Path::new(&config.file_path), &config.file_types, false) {
+        // Symbolic links found while walking must not lead out of the root either.
+        let file_path = file_path.and_then(|file_path| match file_path.canonicalize() {
+            Ok(resolved_path) if !resolved_path.starts_with(root) => Ok(None),
+            _ => read_lines(config, file_path),
+        });
+        let file_lines = match file_path {
+            Ok(Some(file_lines)) => file_lines,
+            Ok(None) => continue,
+            Err(_) if config.no_messages => continue,
+            Err(error) => return Err(Response::from_run_error(&error, root)),
+        };
+        files_searched += 1;
+        let (file_lines, matching_line_indexes, matching_items) =
+            search_file_lines(config, file_lines);
+        if !matching_line_indexes.is_empty() {
+            files.push(file_json(
+                root,
+                config,
+                &file_lines,
+                &matching_line_indexes,
+                &matching_items,
+            ));
+        }
+    }
+    Ok(format!(
+        "{{\"files_searched\":{files_searched},\"files\":[{}]}}",
+        files.join(",")
+    ))
+}
+
+/// `{"path": …, "binary": …, "matches": [{"line": …, "text": …, "ranges": [[start, end]]}]}`,
+/// with the byte ranges (`start..end`) of the matches in the text.
+fn file_json(
+    root: &Path,
+    config: &Config,
+    file_lines: &FileLines,
+    matching_line_indexes: &[usize],
+    matching_items: &[MatchingItem],
+) -> String {
+    let path = file_lines
+        .file_path
+        .strip_prefix(root)
+        .unwrap_or(&file_lines.file_path)
+        .display()
+        .to_string();
+    if file_lines.is_binary && config.binary_files == BinaryFiles::Binary {
+        return format!(
+            "{{\"path\":{},\"binary\":true,\"matches\":[]}}",
+            json::quote(&path)
+        );
+    }
+    let matching_items = non_overlapping_items(matching_items);
+    let matches: Vec<String> = matching_line_indexes
+        .iter()
+        .map(|line_index| {
+            let ranges: Vec<String> = matching_items
+                .iter()
+                .filter(|item| item.line_index == *line_index)
+                .map(|item| format!("[{},{}]", item.start_index, item.end_index + 1))
+                .collect();
+            format!(
+                "{{\"line\":{},\"text\":{},\"ranges\":[{}]}}",
+                file_lines.line_indexes[*line_index] + 1,
+                json::quote(&file_lines.lines[*line_index]),
+                ranges.join(",")
+            )
+        })
+        .collect();
+    format!(
+        "{{\"path\":{},\"binary\":{},\"matches\":[{}]}}",
+        json::quote(&path),
+        file_lines.is_binary,
+        matches.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root() -> PathBuf {
+        Path::new("tests/fixtures/project").canonicalize().unwrap()
+    }
+
+    #[test]
+    fn decode_query_string() {
+        assert_eq!(
+            parse_query_string("query=search+engine&path=src%2Flib.rs&boolean").unwrap(),
+            vec![
+                ("query".to_string(), "search engine".to_string()),
+                ("path".to_string(), "src/lib.rs".to_string()),
+                ("boolean".to_string(), String::new()),
+            ]
+        );
+        assert_eq!(
+            parse_query_string("query=%C3%A9t%C3%A9").unwrap()[0].1,
+            "été"
+        );
+        assert!(parse_query_string("query=%2").is_err());
+        assert!(parse_query_string("query=%zz").is_err());
+    }
+
+    #[test]
+    fn resolve_path_inside_root() {
+        let root = root();
+        assert_eq!(
+            resolve_path(&root, "src/lib.rs"),
+            Ok(root.join("src/lib.rs"))
+        );
+        assert_eq!(resolve_path(&root, "."), Ok(root.clone()));
+        for path in ["../../poem.txt", "src/../../README.md", "/etc/passwd"] {
+            assert_eq!(resolve_path(&root, path).unwrap_err().status, 403, "{path}");
+        }
+        assert_eq!(resolve_path(&root, "missing.txt").unwrap_err().status, 404);
+    }
+
+    #[test]
+    fn build_config_from_parameters() {
+        let root = root();
+        let parameters = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+        let config = build_config(
+            &root,
+            &parameters(&[
+                ("query", "-engine"),
+                ("type", "rust"),
+                ("boolean", "false"),
+                ("ignore_case", "true"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.query, "-engine");
+        assert_eq!(config.file_path, root.display().to_string());
+        assert_eq!(config.file_types.selected, vec!["rust"]);
+        assert_eq!(config.boolean_query, None);
+        assert!(config.ignore_case);
+
+        for pairs in [
+            &[("path", "src")][..],
+            &[("query", "engine"), ("interactive", "true")],
+            &[("query", "engine"), ("boolean", "yes")],
+        ] {
+            assert_eq!(
+                build_config(&root, &parameters(pairs)).unwrap_err().status,
+                400
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const HISTOGRAM_WIDTH: usize = 40;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Stats {
+    pub files_searched: usize,
+    pub bytes_scanned: u64,
+    pub matched_lines: usize,
+    pub matches_per_file: Vec<(String, usize)>,
+    /// Number of matched lines per hour (`YYYY-MM-DD HH:00`), for lines starting with a timestamp.
+    pub matched_lines_per_hour: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    pub fn add_file(
+        &mut self,
+        path: &str,
+        bytes_count: u64,
+        matched_lines: &[&str],
+        matches_count: usize,
+    ) {
+        self.files_searched += 1;
+        self.bytes_scanned += bytes_count;
+        self.matched_lines += matched_lines.len();
+        if matches_count > 0 {
+            self.matches_per_file
+                .push((path.to_string(), matches_count));
+        }
+        for hour in matched_lines.iter().filter_map(|line| timestamp_hour(line)) {
+            *self.matched_lines_per_hour.entry(hour).or_insert(0) += 1;
+        }
+    }
+
+    pub fn print(&self, elapsed_time: Duration) {
+        println!();
+        println!("{} files searched", self.files_searched);
+        println!("{} bytes scanned", self.bytes_scanned);
+        println!("{} lines matched", self.matched_lines);
+        println!("{:.6} seconds elapsed", elapsed_time.as_secs_f64());
+        if !self.matches_per_file.is_empty() {
+            println!();
+            println!("Matches per file:");
+            for (path, matches_count) in &self.matches_per_file {
+                println!("{matches_count:>8} {path}");
+            }
+        }
+        if !self.matched_lines_per_hour.is_empty() {
+            println!();
+            println!("Matched lines per hour:");
+            for line in self.histogram() {
+                println!("{line}");
+            }
+        }
+    }
+
+    pub fn histogram(&self) -> Vec<String> {
+        let maximum = self
+            .matched_lines_per_hour
+            .values()
+            .copied()
+            .max()
+            .unwrap_or(0);
+        self.matched_lines_per_hour
+            .iter()
+            .map(|(hour, count)| {
+                let width = (count * HISTOGRAM_WIDTH).div_ceil(maximum);
+                format!("{hour} | {} {count}", "#".repeat(width))
+            })
+            .collect()
+    }
+}
+
+/// Returns the hour (`YYYY-MM-DD HH:00`) of a line starting with an ISO 8601 timestamp like
+/// `2023-10-01T14:05:00Z`, `2023-10-01 14:05` or `[2023-10-01 14:05:00]`.
+pub fn timestamp_hour(line: &str) -> Option<String> {
+    let line = line.strip_prefix('[').unwrap_or(line);
+    let timestamp = line.get(..16)?.as_bytes();
+    let is_digit = |indexes: &[usize]| {
+        indexes
+            .iter()
+            .all(|index| timestamp[*index].is_ascii_digit())
+    };
+    let is_valid = is_digit(&[0, 1, 2, 3, 5, 6, 8, 9, 11, 12, 14, 15])
+        && timestamp[4] == b'-'
+        && timestamp[7] == b'-'
+        && (timestamp[10] == b'T' || timestamp[10] == b' ')
+        && timestamp[13] == b':';
+    if is_valid {
+        Some(format!("{} {}:00", &line[..10], &line[11..13]))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_timestamp_hour() {
+        assert_eq!(
+            timestamp_hour("2023-10-01T14:05:00Z ERROR disk full"),
+            Some("2023-10-01 14:00".to_string())
+        );
+        assert_eq!(
+            timestamp_hour("[2023-10-01 09:59:59] ERROR"),
+            Some("2023-10-01 09:00".to_string())
+        );
+        assert_eq!(timestamp_hour("ERROR 2023-10-01T14:05:00Z"), None);
+        assert_eq!(timestamp_hour("2023-10-01"), None);
+    }
+
+    #[test]
+    fn add_files_and_histogram() {
+        let mut stats = Stats::default();
+        stats.add_file(
+            "app.log",
+            100,
+            &[
+                "2023-10-01T14:05:00Z ERROR a",
+                "2023-10-01T14:30:00Z ERROR b",
+                "2023-10-01T15:00:00Z ERROR c",
+            ],
+            3,
+        );
+        stats.add_file("empty.log", 10, &[], 0);
+        assert_eq!(stats.files_searched, 2);
+        assert_eq!(stats.bytes_scanned, 110);
+        assert_eq!(stats.matched_lines, 3);
+        assert_eq!(stats.matches_per_file, vec![("app.log".to_string(), 3)]);
+        assert_eq!(
+            stats.histogram(),
+            vec![
+                format!("2023-10-01 14:00 | {} 2", "#".repeat(40)),
+                format!("2023-10-01 15:00 | {} 1", "#".repeat(20)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::arguments::{self, ArgumentValue, ParsedArgument, ARGUMENTS};
+use crate::config::Config;
+use crate::error::RunError;
+use crate::json::{self, JsonValue, Spanned};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::iter;
+use std::path::PathBuf;
+
+/// Number of the latest searches read from the history.
+pub const HISTORY_SIZE: usize = 100;
+
+const SEARCHES_FILE_NAME: &str = "searches.json";
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// Saved searches and history of the searches, in `$MINIGREP_HOME`, or `minigrep` in
+/// `$XDG_DATA_HOME` or `~/.local/share`.
+///
+/// The saved searches are a JSON object mapping each name to its arguments, the history has the
+/// arguments of a search per line.
+#[derive(Debug, PartialEq)]
+pub struct Store {
+    pub directory: PathBuf,
+}
+
+impl Store {
+    pub fn open() -> Result<Store, RunError> {
+        let directory = if let Some(directory) = env::var_os("MINIGREP_HOME") {
+            PathBuf::from(directory)
+        } else if let Some(data_directory) = env::var_os("XDG_DATA_HOME") {
+            PathBuf::from(data_directory).join("minigrep")
+        } else if let Some(home) = env::var_os("HOME") {
+            PathBuf::from(home).join(".local/share/minigrep")
+        } else {
+            return Err(RunError::BadConfig(
+                "Error: No home directory, set `MINIGREP_HOME`.".to_string(),
+            ));
+        };
+        Ok(Store { directory })
+    }
+
+    /// Saves the arguments of a search under `name`, replacing a previous search of this name.
+    pub fn save(&self, name: &str, arguments: &[String]) -> Result<(), RunError> {
+        let mut searches = self.searches()?;
+        match searches
+            .iter_mut()
+            .find(|(saved_name, _)| saved_name == name)
+        {
+            Some((_, saved_arguments)) => *saved_arguments = arguments.to_vec(),
+            None => searches.push((name.to_string(), arguments.to_vec())),
+        }
+        let entries: Vec<String> = searches
+            .iter()
+            .map(|(name, arguments)| format!("  {}: {}", json::quote(name), quote_all(arguments)))
+            .collect();
+        let content = format!("{{\n{}\n}}\n", entries.join(",\n"));
+        let path = self.directory.join(SEARCHES_FILE_NAME);
+        fs::create_dir_all(&self.directory)
+            .and_then(|_| fs::write(&path, content))
+            .map_err(|error| RunError::from_io_error(error, &path))
+    }
+
+    pub fn load(&self, name: &str) -> Result<Vec<String>, RunError> {
+        self.searches()?
+            .into_iter()
+            .find(|(saved_name, _)| saved_name == name)
+            .map(|(_, arguments)| arguments)
+            .ok_or_else(|| RunError::BadConfig(format!("Error: No saved search named `{name}`.")))
+    }
+
+    /// The saved searches, in the order they were first saved.
+    pub fn searches(&self) -> Result<Vec<(String, Vec<String>)>, RunError> {
+        let path = self.directory.join(SEARCHES_FILE_NAME);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(RunError::from_io_error(error, &path)),
+        };
+        let invalid_error = || {
+            RunError::BadConfig(format!(
+                "Error: `{}` is not a valid saved searches file.",
+                path.display()
+            ))
+        };
+        let Some(Spanned {
+            value: JsonValue::Object(entries),
+            ..
+        }) = json::parse(&content)
+        else {
+            return Err(invalid_error());
+        };
+        entries
+            .into_iter()
+            .map(|(name, arguments)| Ok((name, strings(arguments).ok_or_else(invalid_error)?)))
+            .collect()
+    }
+
+    /// Appends the arguments of a search to the history.
+    pub fn record(&self, arguments: &[String]) -> Result<(), RunError> {
+        let path = self.directory.join(HISTORY_FILE_NAME);
+        // A whole line in a single append, so that concurrent searches do not mix their lines.
+        let line = format!("{}\n", quote_all(arguments));
+        fs::create_dir_all(&self.directory)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(&path))
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|error| RunError::from_io_error(error, &path))
+    }
+
+    /// The arguments of the last `HISTORY_SIZE` recorded searches, oldest first. Invalid lines
+    /// are skipped.
+    pub fn history(&self) -> Result<Vec<Vec<String>>, RunError> {
+        let path = self.directory.join(HISTORY_FILE_NAME);
+        let mut history: Vec<Vec<String>> = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| json::parse(line).and_then(strings))
+                .collect(),
+            Err(error) if error.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(RunError::from_io_error(error, &path)),
+        };
+        history.drain(..history.len().saturating_sub(HISTORY_SIZE));
+        Ok(history)
+    }
+}
+
+/// `minigrep save <name> -- <arguments>`.
+#[derive(Debug, PartialEq)]
+pub struct SaveConfig {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+impl SaveConfig {
+    /// Checks the arguments of the search, without the name of the subcommand.
+    pub fn build(mut arguments: impl Iterator<Item = String>) -> Result<SaveConfig, RunError> {
+        let usage_error =
+            || RunError::BadConfig("Usage: minigrep save <name> -- <arguments>".to_string());
+        let name = arguments
+            .next()
+            .filter(|name| !name.is_empty() && !name.starts_with('-'))
+            .ok_or_else(usage_error)?;
+        if arguments.next().as_deref() != Some("--") {
+            return Err(usage_error());
+        }
+        let arguments: Vec<String> = arguments.collect();
+        Config::build(iter::once(String::new()).chain(arguments.iter().cloned()))?;
+        Ok(SaveConfig { name, arguments })
+    }
+}
+
+/// `minigrep run <name> [options]`.
+#[derive(Debug, PartialEq)]
+pub struct RunConfig {
+    pub name: String,
+    /// Added before the arguments of the saved search.
+    pub options: Vec<String>,
+}
+
+impl RunConfig {
+    /// Parses the name and the options of a search, given as `--name value`.
+    pub fn build(arguments: impl Iterator<Item = String>) -> Result<RunConfig, RunError> {
+        let usage_error =
+            || RunError::BadConfig("Usage: minigrep run <name> [options]".to_string());
+        let mut name = None;
+        let mut options = Vec::new();
+        for parsed_argument in arguments::parse(&ARGUMENTS, arguments, usage_error)? {
+            match parsed_argument {
+                ParsedArgument::Positional(argument) if name.is_none() => name = Some(argument),
+                ParsedArgument::Positional(_) => return Err(usage_error()),
+                ParsedArgument::Option(definition, value) => {
+                    options.push(format!("--{}", definition.long));
+                    if definition.value != ArgumentValue::None {
+                        options.push(value);
+                    }
+                }
+            }
+        }
+        Ok(RunConfig {
+            name: name.ok_or_else(usage_error)?,
+            options,
+        })
+    }
+
+    /// The arguments of the saved search, after the options.
+    pub fn arguments(&self, store: &Store) -> Result<Vec<String>, RunError> {
+        let saved_arguments = store.load(&self.name)?;
+        Ok(self
+            .options
+            .iter()
+            .cloned()
+            .chain(saved_arguments)
+            .collect())
+    }
+}
+
+/// `minigrep history`: prints the recorded searches, the latest last.
+pub fn print_history() -> Result<(), RunError> {
+    for (index, arguments) in Store::open()?.history()?.iter().enumerate() {
+        println!("{:>5}  minigrep {}", index + 1, command_line(arguments));
+    }
+    Ok(())
+}
+
+/// Writes the arguments as a shell command line, quoting the arguments that need it.
+pub fn command_line(arguments: &[String]) -> String {
+    let is_safe =
+        |character: char| character.is_ascii_alphanumeric() || "-_./:=,+@%".contains(character);
+    arguments
+        .iter()
+        .map(|argument| {
+            if !argument.is_empty() && argument.chars().all(is_safe) {
+                argument.clone()
+            } else {
+                format!("'{}'", argument.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn quote_all(arguments: &[String]) -> String {
+    let arguments: Vec<String> = arguments
+        .iter()
+        .map(|argument| json::quote(argument))
+        .collect();
+    format!("[{}]", arguments.join(", "))
+}
+
+fn strings(value: Spanned) -> Option<Vec<String>> {
+    let JsonValue::Array(values) = value.value else {
+        return None;
+    };
+    values
+        .into_iter()
+        .map(|value| match value.value {
+            JsonValue::String(string) => Some(string),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_store(name: &str) -> Store {
+        let directory = env::temp_dir().join(format!("minigrep-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        Store { directory }
+    }
+
+    fn arguments(arguments: &[&str]) -> Vec<String> {
+        arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn save_and_load_searches() {
+        let store = temporary_store("searches");
+        assert!(store.searches().unwrap().is_empty());
+        store
+            .save("todos", &arguments(&["--type", "rust", "TODO", "src"]))
+            .unwrap();
+        store
+            .save("quotes", &arguments(&["say \"hi\"", "poem.txt"]))
+            .unwrap();
+        store.save("todos", &arguments(&["TODO", "."])).unwrap();
+        assert_eq!(store.load("todos").unwrap(), arguments(&["TODO", "."]));
+        assert_eq!(
+            store.load("quotes").unwrap(),
+            arguments(&["say \"hi\"", "poem.txt"])
+        );
+        assert_eq!(store.searches().unwrap().len(), 2);
+        assert!(matches!(
+            store.load("missing"),
+            Err(RunError::BadConfig(message)) if message == "Error: No saved search named `missing`."
+        ));
+        fs::remove_dir_all(&store.directory).unwrap();
+    }
+
+    #[test]
+    fn history_keeps_the_latest_searches() {
+        let store = temporary_store("history");
+        for index in 0..HISTORY_SIZE + 2 {
+            store
+                .record(&arguments(&[&index.to_string(), "poem.txt"]))
+                .unwrap();
+        }
+        let history = store.history().unwrap();
+        assert_eq!(history.len(), HISTORY_SIZE);
+        assert_eq!(history[0], arguments(&["2", "poem.txt"]));
+        fs::remove_dir_all(&store.directory).unwrap();
+    }
+
+    #[test]
+    fn quote_command_line() {
+        assert_eq!(
+            command_line(&arguments(&["--type=rust", "it's", "", "src/"])),
+            r"--type=rust 'it'\''s' '' src/"
+        );
+    }
+}
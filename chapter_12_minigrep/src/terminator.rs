@@ -0,0 +1,63 @@
+/// How the content of a file is split into lines.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum LineTerminator {
+    /// `\r\n` or `\n`, the lines are printed with `\n`.
+    #[default]
+    Lf,
+    /// `\r\n` or `\n`, the lines are printed with their own terminator (`--crlf`).
+    Crlf,
+    /// NUL (`--null-data`), e.g. for the output of `find -print0`.
+    Nul,
+}
+
+impl LineTerminator {
+    pub fn separator(self) -> u8 {
+        match self {
+            LineTerminator::Lf | LineTerminator::Crlf => b'\n',
+            LineTerminator::Nul => b'\0',
+        }
+    }
+
+    /// Splits `content` into lines, without their terminators, each with the terminator to print
+    /// after it, see `trim`.
+    pub fn lines(self, content: &str) -> impl Iterator<Item = (&str, &'static str)> {
+        content
+            .split_terminator(self.separator() as char)
+            .map(move |line| self.trim(line))
+    }
+
+    /// Removes the `\r` left at the end of a line split on `\n`, like `str::lines`, and returns the
+    /// terminator to print after the line: its own with `--crlf`, so that the CRLF and LF lines
+    /// of a file are printed as they are in the file.
+    pub fn trim(self, line: &str) -> (&str, &'static str) {
+        match self {
+            LineTerminator::Lf => (line.strip_suffix('\r').unwrap_or(line), "\n"),
+            LineTerminator::Crlf => match line.strip_suffix('\r') {
+                Some(line) => (line, "\r\n"),
+                None => (line, "\n"),
+            },
+            LineTerminator::Nul => (line, "\0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_print_lines() {
+        let content = "frog\r\nbog\n\r\n";
+        let lines: Vec<_> = LineTerminator::Lf.lines(content).collect();
+        assert_eq!(lines, vec![("frog", "\n"), ("bog", "\n"), ("", "\n")]);
+
+        let lines: Vec<_> = LineTerminator::Crlf.lines(content).collect();
+        assert_eq!(lines, vec![("frog", "\r\n"), ("bog", "\n"), ("", "\r\n")]);
+
+        let lines: Vec<_> = LineTerminator::Nul
+            .lines("src/lib.rs\0poem.txt\0")
+            .collect();
+        assert_eq!(lines, vec![("src/lib.rs", "\0"), ("poem.txt", "\0")]);
+        assert_eq!(LineTerminator::Nul.lines("").count(), 0);
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Digests of the lines already printed with `--unique`, so that only 8 bytes are kept per
+/// distinct line. A collision of two 64-bit digests would skip a line, but is very unlikely.
+#[derive(Debug, Default)]
+pub struct UniqueLines {
+    digests: HashSet<u64>,
+}
+
+impl UniqueLines {
+    /// Returns `true` the first time the line is inserted.
+    pub fn insert(&mut self, line: &str) -> bool {
+        let mut hasher = DefaultHasher::new();
+        line.hash(&mut hasher);
+        self.digests.insert(hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_distinct_lines_once() {
+        let mut unique_lines = UniqueLines::default();
+        assert!(unique_lines.insert("How public, like a frog"));
+        assert!(unique_lines.insert("How dreary to be somebody!"));
+        assert!(!unique_lines.insert("How public, like a frog"));
+        assert!(unique_lines.insert("how public, like a frog"));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::archive;
+use crate::error::RunError;
+use crate::file_types::FileTypes;
+use std::fs::{self, DirEntry};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SortKey {
+    Path,
+    Modified,
+    Accessed,
+    Created,
+}
+
+impl SortKey {
+    pub fn parse(value: &str) -> Result<SortKey, String> {
+        match value {
+            "path" => Ok(SortKey::Path),
+            "modified" => Ok(SortKey::Modified),
+            "accessed" => Ok(SortKey::Accessed),
+            "created" => Ok(SortKey::Created),
+            _ => Err(format!(
+                "Error: Cannot sort by `{value}`, expected one of: path, modified, accessed, created."
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Sort {
+    pub key: SortKey,
+    pub is_reversed: bool,
+}
+
+/// Archives are always walked with `search_archives`, their members are filtered by type instead.
+pub fn file_paths(
+    path: &Path,
+    file_types: &FileTypes,
+    search_archives: bool,
+) -> Vec<Result<PathBuf, RunError>> {
+    if !path.is_dir() {
+        return vec![Ok(path.to_path_buf())];
+    }
+    let mut file_paths = Vec::new();
+    walk_directory(
+        path,
+        file_types,
+        search_archives,
+        &mut Vec::new(),
+        &mut file_paths,
+    );
+    file_paths
+}
+
+/// Symbolic links are followed, `ancestors` has the canonical paths of the directories being
+/// walked so that a link to one of them is not walked forever.
+fn walk_directory(
+    directory: &Path,
+    file_types: &FileTypes,
+    search_archives: bool,
+    ancestors: &mut Vec<PathBuf>,
+    file_paths: &mut Vec<Result<PathBuf, RunError>>,
+) {
+    let canonical_directory =
+        fs::canonicalize(directory).unwrap_or_else(|_| directory.to_path_buf());
+    if ancestors.contains(&canonical_directory) {
+        return;
+    }
+    let mut entries = match read_directory(directory) {
+        Ok(entries) => entries,
+        Err(error) => {
+            file_paths.push(Err(RunError::from_io_error(error, directory)));
+            return;
+        }
+    };
+    entries.sort_by_key(|entry| entry.file_name());
+    ancestors.push(canonical_directory);
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let entry_path = entry.path();
+        let is_symlink = entry
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_symlink());
+        match fs::metadata(&entry_path) {
+            Ok(metadata) if metadata.is_dir() => {
+                walk_directory(
+                    &entry_path,
+                    file_types,
+                    search_archives,
+                    ancestors,
+                    file_paths,
+                );
+            }
+            Ok(_) => {
+                let is_archive = search_archives && archive::is_archive(&entry_path);
+                if is_archive || file_types.is_match(&file_name) {
+                    file_paths.push(Ok(entry_path));
+                }
+            }
+            // A dangling link is not an error of the search.
+            Err(_) if is_symlink => continue,
+            Err(error) => file_paths.push(Err(RunError::from_io_error(error, &entry_path))),
+        }
+    }
+    ancestors.pop();
+}
+
+fn read_directory(directory: &Path) -> Result<Vec<DirEntry>, std::io::Error> {
+    fs::read_dir(directory)?.collect()
+}
+
+/// Sorts the file paths across directories, the files whose time is not available (and the
+/// errors) come first, and the files with the same time are sorted by path.
+pub fn sort_file_paths(file_paths: &mut [Result<PathBuf, RunError>], sort: Sort) {
+    file_paths.sort_by_cached_key(|file_path| {
+        let Ok(file_path) = file_path else {
+            return (None, None);
+        };
+        let metadata = fs::metadata(file_path);
+        let time: Option<SystemTime> = match sort.key {
+            SortKey::Path => None,
+            SortKey::Modified => metadata.and_then(|metadata| metadata.modified()).ok(),
+            SortKey::Accessed => metadata.and_then(|metadata| metadata.accessed()).ok(),
+            SortKey::Created => metadata.and_then(|metadata| metadata.created()).ok(),
+        };
+        (time, Some(file_path.clone()))
+    });
+    if sort.is_reversed {
+        file_paths.reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_by_path_across_directories() {
+        let mut file_paths = vec![
+            Ok(PathBuf::from("src/main.rs")),
+            Ok(PathBuf::from("README.md")),
+            Ok(PathBuf::from("src/lib.rs")),
+        ];
+        let sort = Sort {
+            key: SortKey::Path,
+            is_reversed: true,
+        };
+        sort_file_paths(&mut file_paths, sort);
+        let file_paths: Vec<PathBuf> = file_paths.into_iter().map(Result::unwrap).collect();
+        assert_eq!(
+            file_paths,
+            vec![
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("README.md"),
+            ]
+        );
+    }
+}
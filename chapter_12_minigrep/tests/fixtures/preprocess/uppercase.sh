@@ -0,0 +1,3 @@
+#!/bin/sh
+# Prints the file in uppercase, to test `--pre`.
+tr "[:lower:]" "[:upper:]" < "$1"
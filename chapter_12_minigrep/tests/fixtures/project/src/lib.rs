@@ -0,0 +1,4 @@
+// The search engine of the project.
+pub fn search(query: &str) -> bool {
+    query.is_empty()
+}
@@ -0,0 +1,7 @@
+/// Parses a query, e.g. `query:"frog"`.
+pub fn parse_query(text: &str) -> Option<&str> {
+    /* A query without the `query:` prefix
+       is not a query. */
+    let query = text.strip_prefix("query:")?;
+    Some(query.trim_matches('"'))
+}
@@ -0,0 +1,75 @@
+//! Golden-file tests: each directory of `tests/cases` is a test case made of:
+//!
+//! - `args`: the command line arguments, one per line.
+//! - `input` (optional): written to stdin, the command is run in the case directory so `input`
+//!   can also be used as a file path in `args`.
+//! - `expected_stdout` and `expected_stderr` (optional): the expected outputs.
+//! - `exit_code` (optional, `0` by default): the expected exit code.
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+
+fn run_case(case_path: &Path) -> Result<(), String> {
+    let read = |file_name: &str| fs::read_to_string(case_path.join(file_name)).ok();
+    let args = read("args").ok_or("missing `args` file")?;
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command
+        .current_dir(case_path)
+        .env_remove("IGNORE_CASE")
+        .args(args.lines());
+    if let Some(input) = read("input") {
+        command.write_stdin(input);
+    }
+    let output = command.output().map_err(|error| error.to_string())?;
+    let mut errors = Vec::new();
+    let expected_exit_code: i32 = read("exit_code")
+        .unwrap_or("0".to_string())
+        .trim()
+        .parse()
+        .map_err(|_| "invalid `exit_code` file")?;
+    if output.status.code() != Some(expected_exit_code) {
+        errors.push(format!(
+            "exit code: expected {expected_exit_code}, got {:?}",
+            output.status.code()
+        ));
+    }
+    for (file_name, actual) in [
+        ("expected_stdout", &output.stdout),
+        ("expected_stderr", &output.stderr),
+    ] {
+        if let Some(expected) = read(file_name) {
+            let actual = String::from_utf8_lossy(actual);
+            if actual != expected {
+                errors.push(format!(
+                    "{file_name}:\n--- expected\n{expected}\n--- actual\n{actual}"
+                ));
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+#[test]
+fn test_golden_cases() {
+    let mut case_paths: Vec<_> = fs::read_dir("tests/cases")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    case_paths.sort();
+    assert!(!case_paths.is_empty(), "no test cases in `tests/cases`");
+    let failures: Vec<String> = case_paths
+        .iter()
+        .filter_map(|case_path| {
+            run_case(case_path)
+                .err()
+                .map(|error| format!("case `{}` failed:\n{error}", case_path.display()))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use predicates::prelude::*;
 
 #[test]
 fn test_main_success_default() {
@@ -39,6 +40,7 @@ fn test_main_failure_invalid_arguments() {
     command
         .assert()
         .failure()
+        .code(64)
         .stderr("Usage: minigrep <query> <file_path>\n");
 }
 
@@ -49,5 +51,649 @@ fn test_main_failure_invalid_file_path() {
     command
         .assert()
         .failure()
+        .code(66)
         .stderr("Error: File `invalid_file_path.txt` not found.\n");
 }
+
+#[test]
+fn test_main_success_directory() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["search", "tests/fixtures/project"]);
+    command.assert().success().stdout(
+        "tests/fixtures/project/Cargo.lock:name = \"search\"\ntests/fixtures/project/Cargo.toml:name = \"search\"\ntests/fixtures/project/README.md:The search engine is written in Rust.\ntests/fixtures/project/src/lib.rs:// The search engine of the project.\ntests/fixtures/project/src/lib.rs:pub fn search(query: &str) -> bool {\n",
+    );
+}
+
+#[test]
+fn test_main_success_file_types() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "-t",
+        "rust",
+        "-t",
+        "markdown",
+        "search",
+        "tests/fixtures/project",
+    ]);
+    command.assert().success().stdout(
+        "tests/fixtures/project/README.md:The search engine is written in Rust.\ntests/fixtures/project/src/lib.rs:// The search engine of the project.\ntests/fixtures/project/src/lib.rs:pub fn search(query: &str) -> bool {\n",
+    );
+}
+
+#[test]
+fn test_main_success_file_types_negated_and_added() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--type-add",
+        "manifest:Cargo.*",
+        "-T",
+        "manifest",
+        "-T",
+        "rust",
+        "search",
+        "tests/fixtures/project",
+    ]);
+    command
+        .assert()
+        .success()
+        .stdout("tests/fixtures/project/README.md:The search engine is written in Rust.\n");
+}
+
+#[test]
+fn test_main_failure_unknown_file_type() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["-t", "unknown", "search", "tests/fixtures/project"]);
+    command
+        .assert()
+        .failure()
+        .stderr("Error: Unknown file type.\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_main_success_directory_symlinks() {
+    use std::os::unix::fs::symlink;
+    let directory = std::env::temp_dir().join(format!("minigrep-links-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(directory.join("real")).unwrap();
+    std::fs::write(directory.join("real/frog.txt"), "frog\n").unwrap();
+    symlink("real", directory.join("link")).unwrap();
+    symlink(".", directory.join("real/loop")).unwrap();
+    symlink("missing", directory.join("dangling")).unwrap();
+
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.current_dir(&directory).args(["frog", "."]);
+    command
+        .assert()
+        .success()
+        .stdout("./link/frog.txt:frog\n./real/frog.txt:frog\n");
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_main_failure_invalid_utf8() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["search", "tests/fixtures/encodings"]);
+    command
+        .assert()
+        .failure()
+        .code(65)
+        .stdout("")
+        .stderr("Error: File `tests/fixtures/encodings/latin1.txt` is not valid UTF-8.\n");
+}
+
+#[test]
+fn test_main_success_no_messages() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--no-messages", "search", "tests/fixtures/encodings"]);
+    command
+        .assert()
+        .success()
+        .stdout("tests/fixtures/encodings/utf8.txt:café search\n")
+        .stderr("");
+}
+
+#[test]
+fn test_main_success_mmap() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--mmap", "the", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("Then there\'s a pair of us - don\'t tell!\nTo tell your name the livelong day\n");
+}
+
+#[test]
+fn test_main_success_only_matching() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["-o", "tell", "poem.txt"]);
+    command.assert().success().stdout("tell\ntell\n");
+}
+
+#[test]
+fn test_main_success_format() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--format", "{path}:{line}:{col}:{match}", "the", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("poem.txt:3:6:the\npoem.txt:8:19:the\n");
+}
+
+#[test]
+fn test_main_failure_unknown_format_placeholder() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--format", "{file}", "the", "poem.txt"]);
+    command.assert().failure().code(64);
+}
+
+#[test]
+fn test_main_success_field() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--field", "2", "France", "tests/fixtures/data/cities.csv"]);
+    command.assert().success().stdout(
+        "city,country,note\nParis,France,\"Capital of France, on the Seine\"\nLyon,France,\"Known for \"\"bouchons\"\"\"\nNice,\"France\",Paris is far\n",
+    );
+}
+
+#[test]
+fn test_main_success_field_multiline_record() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--field", "3", "France", "tests/fixtures/data/cities.csv"]);
+    command.assert().success().stdout(
+        "city,country,note\nParis,France,\"Capital of France, on the Seine\"\nGeneva,Switzerland,\"Lake\nnear France\"\n",
+    );
+}
+
+#[test]
+fn test_main_success_field_delimiter() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--field",
+        "1",
+        "--delimiter",
+        "\\t",
+        "France",
+        "tests/fixtures/data/cities.tsv",
+    ]);
+    command
+        .assert()
+        .success()
+        .stdout("city\tcountry\nFrance\tNowhere\n");
+}
+
+#[test]
+fn test_main_success_json_path() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--json-path",
+        ".error.message",
+        "disk",
+        "tests/fixtures/data/app.log",
+    ]);
+    command.assert().success().stdout(
+        "{\"level\":\"error\",\"error\":{\"message\":\"disk full\",\"code\":28},\"host\":\"disk-1\"}\n",
+    );
+}
+
+#[test]
+fn test_main_success_json_predicate() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--where",
+        "level=error",
+        "disk",
+        "tests/fixtures/data/app.log",
+    ]);
+    command.assert().success().stdout(
+        "{\"level\":\"error\",\"error\":{\"message\":\"disk full\",\"code\":28},\"host\":\"disk-1\"}\n{\"level\":\"error\",\"error\":{\"message\":\"timeout\"},\"host\":\"disk-2\"}\n",
+    );
+}
+
+#[test]
+fn test_main_success_boolean_query() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--boolean", "(us OR frog) AND NOT banish", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("Then there\'s a pair of us - don\'t tell!\nHow public, like a frog\n");
+}
+
+#[test]
+fn test_main_failure_invalid_boolean_query() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--boolean", "(us OR frog", "poem.txt"]);
+    command
+        .assert()
+        .failure()
+        .code(65)
+        .stderr("Error: Invalid pattern `(us OR frog`: missing `)`.\n");
+}
+
+#[test]
+fn test_main_failure_deeply_nested_boolean_query() {
+    let query = format!("{}frog", "(".repeat(100_000));
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--boolean", &query, "poem.txt"]);
+    command
+        .assert()
+        .failure()
+        .code(65)
+        .stderr(predicate::str::ends_with(": query nested too deeply.\n"));
+}
+
+#[test]
+fn test_main_success_diff() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--diff", "tests/fixtures/data/changes.patch", "query"]);
+    command.assert().success().stdout(
+        "src/search.rs:2:-    // TODO: search the query\nsrc/search.rs:2:+    // Search the query in the lines.\nsrc/search.rs:3:+    query.is_empty()\nsrc/search.rs:21:+    println!(\"{query}\");\n",
+    );
+}
+
+#[test]
+fn test_main_success_diff_stdin() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--diff", "-", "TODO"]);
+    command.write_stdin(include_str!("fixtures/data/changes.patch"));
+    command
+        .assert()
+        .success()
+        .stdout("src/search.rs:2:-    // TODO: search the query\n");
+}
+
+#[test]
+fn test_main_success_stats() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--stats", "ERROR", "tests/fixtures/data/server.log"]);
+    command.assert().success().stdout(
+        predicate::str::starts_with(
+            "2023-10-01T14:05:00Z ERROR disk full\n2023-10-01T14:45:00Z ERROR disk full again\n2023-10-01T15:01:00Z ERROR timeout\n\n1 files searched\n154 bytes scanned\n3 lines matched\n",
+        )
+        .and(predicate::str::contains("seconds elapsed\n"))
+        .and(predicate::str::ends_with(format!(
+            "\nMatches per file:\n       3 tests/fixtures/data/server.log\n\nMatched lines per hour:\n2023-10-01 14:00 | {} 2\n2023-10-01 15:00 | {} 1\n",
+            "#".repeat(40),
+            "#".repeat(20)
+        ))),
+    );
+}
+
+#[test]
+fn test_main_success_generate() {
+    for (target, expected) in [
+        ("bash", "complete -o filenames -F _minigrep minigrep"),
+        ("zsh", "#compdef minigrep"),
+        ("fish", "complete -c minigrep -s o -l only-matching"),
+        ("man", ".TH MINIGREP 1"),
+    ] {
+        let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        command.args(["generate", target]);
+        command
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected));
+    }
+}
+
+#[test]
+fn test_main_failure_generate_unknown_target() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["generate", "powershell"]);
+    command
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("Error: Cannot generate `powershell`, expected one of: bash, zsh, fish, man.\n");
+}
+
+#[test]
+fn test_main_success_command_name_query() {
+    let input = "save run history serve lsp generate\n";
+    for name in ["save", "run", "history", "serve", "lsp", "generate"] {
+        let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        command.args(["--", name, "-"]).write_stdin(input);
+        command.assert().success().stdout(input);
+    }
+}
+
+#[test]
+fn test_main_failure_command_name_query() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["history", "poem.txt"]);
+    command
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("Usage: minigrep history\n");
+}
+
+#[test]
+fn test_main_success_binary_file_matches() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["frog", "tests/fixtures/binary"]);
+    command.assert().success().stdout(
+        "Binary file tests/fixtures/binary/image.bin matches\n\
+         tests/fixtures/binary/notes.txt:A frog in the bog.\n",
+    );
+}
+
+#[test]
+fn test_main_success_binary_files_without_match() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--binary-files=without-match",
+        "frog",
+        "tests/fixtures/binary",
+    ]);
+    command
+        .assert()
+        .success()
+        .stdout("tests/fixtures/binary/notes.txt:A frog in the bog.\n");
+}
+
+#[test]
+fn test_main_success_binary_file_as_text() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["-a", "public", "tests/fixtures/binary/image.bin"]);
+    command
+        .assert()
+        .success()
+        .stdout("how public, like a frog\n");
+}
+
+#[test]
+fn test_main_success_search_archives() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["-z", "-t", "rust", "engine", "tests/fixtures/archives"]);
+    command.assert().success().stdout(
+        "tests/fixtures/archives/bundle.tar!/src/lib.rs:1:// The search engine of the project.\n\
+         tests/fixtures/archives/bundle.tar.gz!/src/lib.rs:1:// The search engine of the project.\n\
+         tests/fixtures/archives/bundle.zip!/src/lib.rs:1:// The search engine of the project.\n",
+    );
+}
+
+#[test]
+fn test_main_success_search_archive_file() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--search-archives",
+        "Rust",
+        "tests/fixtures/archives/bundle.tar.gz",
+    ]);
+    command.assert().success().stdout(
+        "tests/fixtures/archives/bundle.tar.gz!/README.md:3:The search engine is written in Rust.\n",
+    );
+}
+
+#[test]
+fn test_main_success_lines_range() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--lines", "3:7", "o", "poem.txt"]);
+    command.assert().success().stdout(
+        "Then there's a pair of us - don't tell!\n\
+         They'd banish us, you know.\n\
+         How dreary to be somebody!\n\
+         How public, like a frog\n",
+    );
+}
+
+#[test]
+fn test_main_success_max_columns() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--max-columns", "10", "frog", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("How public [... 13 more bytes]\n");
+}
+
+#[test]
+fn test_main_failure_invalid_lines_range() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--lines", "7:3", "o", "poem.txt"]);
+    command
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("Error: Invalid line range `7:3`, expected START:END like 100:200.\n");
+}
+
+#[test]
+fn test_main_success_sort_reversed_path() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--sortr", "path", "engine", "tests/fixtures/project"]);
+    command.assert().success().stdout(
+        "tests/fixtures/project/src/lib.rs:// The search engine of the project.\n\
+         tests/fixtures/project/README.md:The search engine is written in Rust.\n",
+    );
+}
+
+#[test]
+fn test_main_success_unique_lines() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["-z", "--unique", "engine", "tests/fixtures/archives"]);
+    command.assert().success().stdout(
+        "tests/fixtures/archives/bundle.tar!/README.md:3:The search engine is written in Rust.\n\
+         tests/fixtures/archives/bundle.tar!/src/lib.rs:1:// The search engine of the project.\n",
+    );
+}
+
+#[test]
+fn test_main_success_preprocessor() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--pre",
+        "tests/fixtures/preprocess/uppercase.sh",
+        "--pre-glob",
+        "*.txt",
+        "FROG",
+        "poem.txt",
+    ]);
+    command
+        .assert()
+        .success()
+        .stdout("HOW PUBLIC, LIKE A FROG\n");
+}
+
+#[test]
+fn test_main_failure_preprocessor() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--pre", "false", "frog", "poem.txt"]);
+    command
+        .assert()
+        .failure()
+        .code(74)
+        .stderr("Error: The preprocessor `false` failed on `poem.txt` (exit status: 1).\n");
+}
+
+#[test]
+fn test_main_success_passthru() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--passthru", "--lines", "3:5", "frog", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("Then there's a pair of us - don't tell!\nThey'd banish us, you know.\n\n");
+}
+
+#[test]
+fn test_main_success_passthru_mmap() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--mmap", "--passthru", "--lines", "5:7", "frog", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("\nHow dreary to be somebody!\nHow public, like a frog\n");
+}
+
+#[test]
+fn test_main_failure_passthru_only_matching() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--passthru", "-o", "frog", "poem.txt"]);
+    command
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("Error: `--passthru` cannot be used with `--only-matching` or `--format`.\n");
+}
+
+#[test]
+fn test_main_success_crlf() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["bog", "tests/fixtures/line_endings/crlf.txt"]);
+    command.assert().success().stdout("To an admiring bog!\n");
+
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--crlf", "bog", "tests/fixtures/line_endings/crlf.txt"]);
+    command.assert().success().stdout("To an admiring bog!\r\n");
+
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--format",
+        "[{text}]",
+        "bog",
+        "tests/fixtures/line_endings/crlf.txt",
+    ]);
+    command.assert().success().stdout("[To an admiring bog!]\n");
+}
+
+#[test]
+fn test_main_success_crlf_mixed_line_endings() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["--crlf", "bog", "tests/fixtures/line_endings/mixed.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("To an admiring bog!\nThe frog and the bog\r\n");
+}
+
+#[test]
+fn test_main_success_null_data() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args([
+        "--null-data",
+        "src/",
+        "tests/fixtures/line_endings/paths.txt",
+    ]);
+    command
+        .assert()
+        .success()
+        .stdout("src/frog.rs\0src/main.rs\0");
+}
+
+#[test]
+fn test_main_success_null_file_paths() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.args(["-0", "--crlf", "public", "tests/fixtures/line_endings"]);
+    command
+        .assert()
+        .success()
+        .stdout("tests/fixtures/line_endings/crlf.txt\0How public, like a frog\r\n");
+}
+
+#[test]
+fn test_main_success_saved_searches_and_history() {
+    let home = std::env::temp_dir().join(format!("minigrep-home-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&home);
+    let minigrep = |arguments: &[&str]| {
+        let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        command
+            .env("MINIGREP_HOME", &home)
+            .env("MINIGREP_HISTORY", "true")
+            .args(arguments);
+        command
+    };
+
+    minigrep(&["save", "frogs", "--", "frog", "poem.txt"])
+        .assert()
+        .success()
+        .stdout("Saved search `frogs`.\n");
+    minigrep(&["run", "frogs"])
+        .assert()
+        .success()
+        .stdout("How public, like a frog\n");
+    minigrep(&["run", "frogs", "--format", "{line}"])
+        .assert()
+        .success()
+        .stdout("7\n");
+    minigrep(&["run", "toads"])
+        .assert()
+        .failure()
+        .code(64)
+        .stderr("Error: No saved search named `toads`.\n");
+    minigrep(&["history"])
+        .assert()
+        .success()
+        .stdout("    1  minigrep frog poem.txt\n    2  minigrep --format '{line}' frog poem.txt\n");
+
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command
+        .env("MINIGREP_HOME", &home)
+        .args(["bog", "poem.txt"]);
+    command.assert().success();
+    minigrep(&["history"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("bog").not());
+    std::fs::remove_dir_all(&home).unwrap();
+}
+
+#[test]
+fn test_main_success_history_not_recorded() {
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command
+        .env("MINIGREP_HOME", "poem.txt")
+        .env("MINIGREP_HISTORY", "true")
+        .args(["frog", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("How public, like a frog\n")
+        .stderr(predicate::str::starts_with(
+            "Warning: The search is not recorded in the history: ",
+        ));
+}
+
+#[test]
+fn test_main_success_scope() {
+    let cases = [
+        (
+            "code",
+            "pub fn parse_query(text: &str) -> Option<&str> {\n    let query = text.strip_prefix(\"query:\")?;\n    Some(query.trim_matches('\"'))\n",
+        ),
+        (
+            "comments",
+            "/// Parses a query, e.g. `query:\"frog\"`.\n    /* A query without the `query:` prefix\n       is not a query. */\n",
+        ),
+        (
+            "strings",
+            "    let query = text.strip_prefix(\"query:\")?;\n",
+        ),
+    ];
+    for (scope, expected_stdout) in cases {
+        let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        command.args(["--scope", scope, "query", "tests/fixtures/scope/query.rs"]);
+        command.assert().success().stdout(expected_stdout);
+    }
+}
+
+#[test]
+fn test_main_success_global_options() {
+    let home = std::env::temp_dir().join(format!("minigrep-global-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&home);
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command
+        .env("MINIGREP_HOME", &home)
+        .env("MINIGREP_HISTORY", "true")
+        .args(["--color", "always", "--no-history", "frog", "poem.txt"]);
+    command
+        .assert()
+        .success()
+        .stdout("How public, like a \u{1b}[1;31mfrog\u{1b}[0m\n");
+
+    let mut command = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    command.env("MINIGREP_HOME", &home).arg("history");
+    command.assert().success().stdout("");
+}
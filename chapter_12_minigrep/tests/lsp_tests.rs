@@ -0,0 +1,104 @@
+use assert_cmd::cargo::CommandCargoExt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{ChildStdout, Command, Stdio};
+
+fn send(stdin: &mut impl Write, message: &str) {
+    write!(stdin, "Content-Length: {}\r\n\r\n{message}", message.len()).unwrap();
+    stdin.flush().unwrap();
+}
+
+fn receive(stdout: &mut BufReader<ChildStdout>) -> String {
+    let mut header = String::new();
+    stdout.read_line(&mut header).unwrap();
+    let content_length: usize = header
+        .trim_end()
+        .strip_prefix("Content-Length: ")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut separator = String::new();
+    stdout.read_line(&mut separator).unwrap();
+    let mut content = vec![0; content_length];
+    stdout.read_exact(&mut content).unwrap();
+    String::from_utf8(content).unwrap()
+}
+
+#[test]
+fn text_search_streams_progress_notifications() {
+    let mut process = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = process.stdin.take().unwrap();
+    let mut stdout = BufReader::new(process.stdout.take().unwrap());
+    let root = Path::new("tests/fixtures/project").canonicalize().unwrap();
+
+    send(
+        &mut stdin,
+        &format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"initialize","params":{{"rootPath":"{}"}}}}"#,
+            root.display()
+        ),
+    );
+    assert!(
+        receive(&mut stdout).starts_with(r#"{"jsonrpc":"2.0","id":1,"result":{"capabilities":"#)
+    );
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+    );
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":2,"method":"workspace/textSearch","params":{"query":"engine","types":["rust"],"partialResultToken":"search-1"}}"#,
+    );
+    let uri = format!("file://{}/src/lib.rs", root.display());
+    assert_eq!(
+        receive(&mut stdout),
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"$/progress","params":{{"token":"search-1","value":[{{"uri":"{uri}","range":{{"start":{{"line":0,"character":14}},"end":{{"line":0,"character":20}}}},"text":"// The search engine of the project."}}]}}}}"#
+        )
+    );
+    assert_eq!(
+        receive(&mut stdout),
+        r#"{"jsonrpc":"2.0","id":2,"result":[]}"#
+    );
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":"3","method":"workspace/textSearch","params":{"query":"Rust","path":"README.md"}}"#,
+    );
+    let response = receive(&mut stdout);
+    assert!(response.starts_with(r#"{"jsonrpc":"2.0","id":"3","result":[{"uri":"#));
+    assert!(response.contains(r#""start":{"line":2,"character":32}"#));
+
+    send(
+        &mut stdin,
+        r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#,
+    );
+    assert_eq!(
+        receive(&mut stdout),
+        r#"{"jsonrpc":"2.0","id":4,"result":null}"#
+    );
+    send(&mut stdin, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    assert!(process.wait().unwrap().success());
+}
+
+#[test]
+fn exit_without_shutdown_fails() {
+    let mut process = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = process.stdin.take().unwrap();
+    send(&mut stdin, r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    assert_eq!(process.wait().unwrap().code(), Some(1));
+}
@@ -0,0 +1,41 @@
+use chapter_12_minigrep::search::{search, MatchingItem};
+use proptest::prelude::*;
+
+/// Reference implementation: every byte index where the line starts with the query.
+fn naive_search(query: &str, lines: &[String]) -> Vec<MatchingItem> {
+    let mut matching_items = Vec::new();
+    for (line_index, line) in lines.iter().enumerate() {
+        for start_index in 0..line.len() {
+            if line.is_char_boundary(start_index) && line[start_index..].starts_with(query) {
+                matching_items.push(MatchingItem {
+                    start_index,
+                    end_index: start_index + query.len() - 1,
+                    line_index,
+                });
+            }
+        }
+    }
+    matching_items
+}
+
+// A small alphabet mixing 1 to 4 bytes characters, so that random queries often match.
+const TEXT_PATTERN: &str = "[ab é日🦀]{0,30}";
+const QUERY_PATTERN: &str = "[ab é日🦀]{1,3}";
+
+proptest! {
+    #[test]
+    fn search_agrees_with_naive_search(
+        query in QUERY_PATTERN,
+        lines in prop::collection::vec(TEXT_PATTERN, 0..8),
+    ) {
+        prop_assert_eq!(search(&query, &lines), naive_search(&query, &lines));
+    }
+
+    #[test]
+    fn search_agrees_with_naive_search_any_unicode(
+        query in "\\PC{1,2}",
+        lines in prop::collection::vec("\\PC{0,20}", 0..4),
+    ) {
+        prop_assert_eq!(search(&query, &lines), naive_search(&query, &lines));
+    }
+}
@@ -0,0 +1,113 @@
+use assert_cmd::cargo::CommandCargoExt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+
+/// A `minigrep serve` process on a free port, killed when dropped.
+struct Server {
+    process: Child,
+    address: String,
+}
+
+impl Server {
+    fn start(timeout_seconds: &str) -> Server {
+        let mut process = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+            .unwrap()
+            .args([
+                "serve",
+                "--root",
+                "tests/fixtures/project",
+                "--port",
+                "0",
+                "--timeout",
+                timeout_seconds,
+            ])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(process.stdout.as_mut().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim_end()
+            .strip_prefix("Listening on http://")
+            .expect("the server should print its address")
+            .to_string();
+        Server { process, address }
+    }
+
+    /// Sends the raw request and returns the status code and the body of the response.
+    fn send(&self, request: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(&self.address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    fn get(&self, target: &str) -> (u16, String) {
+        self.send(&format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n"))
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+#[test]
+fn search_returns_json_matches() {
+    let server = Server::start("10");
+    assert_eq!(
+        server.get("/search?query=engine&type=rust"),
+        (
+            200,
+            r#"{"files_searched":1,"files":[{"path":"src/lib.rs","binary":false,"matches":[{"line":1,"text":"// The search engine of the project.","ranges":[[14,20]]}]}]}"#
+                .to_string()
+        )
+    );
+    assert_eq!(
+        server.get("/search?query=RUST&path=README.md&ignore_case=true"),
+        (
+            200,
+            r#"{"files_searched":1,"files":[{"path":"README.md","binary":false,"matches":[{"line":3,"text":"The search engine is written in Rust.","ranges":[[32,36]]}]}]}"#
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn search_rejects_invalid_requests() {
+    let server = Server::start("10");
+    let nested_query = format!("/search?query={}engine&boolean=true", "(".repeat(3000));
+    let cases = [
+        ("/search?query=engine&path=..%2F..%2Fpoem.txt", 403),
+        ("/search?query=engine&path=%2Fetc%2Fpasswd", 403),
+        ("/search?query=engine&path=missing.txt", 404),
+        ("/search?path=src", 400),
+        ("/search?query=engine&interactive=true", 400),
+        ("/search?query=(engine&boolean=true", 400),
+        (&nested_query, 400),
+        ("/files", 404),
+    ];
+    for (target, expected_status) in cases {
+        let (status, body) = server.get(target);
+        assert_eq!(status, expected_status, "{target}: {body}");
+        assert!(body.starts_with(r#"{"error":"#), "{target}: {body}");
+    }
+    let (status, _) = server.send("POST /search?query=engine HTTP/1.1\r\n\r\n");
+    assert_eq!(status, 405);
+}
+
+#[test]
+fn incomplete_request_times_out() {
+    let server = Server::start("1");
+    let (status, body) = server.send("GET /search?query=engine HTTP/1.1\r\n");
+    assert_eq!(status, 408);
+    assert_eq!(body, r#"{"error":"request timeout"}"#);
+}